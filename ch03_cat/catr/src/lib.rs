@@ -1,36 +1,87 @@
 use clap::{App, Arg};
-use std::error::Error;
+use common::MyResult;
+use flate2::bufread::MultiGzDecoder;
+use regex::Regex;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
     number_lines: bool,
     number_nonblank_lines: bool,
+    headers: bool,
+    reverse: bool,
+    skip_bytes: u64,
+    pattern: Option<Regex>,
+    buffer_size: usize,
+    show_nonprinting: bool,
+    show_tabs: bool,
+    show_ends: bool,
 }
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
-pub fn run(config: Config) -> MyResult<()> {
-    for filename in config.files {
-        match open(&filename) {
-            Err(err) => eprint!("Failed to open {}: {}", filename, err),
+/// 開けないファイルがあればfalseを返す
+pub fn run(config: Config) -> MyResult<bool> {
+    let mut failed = false;
+    // GNU catと同じく、行番号はファイルをまたいで通しで振る
+    let mut i = 1;
+    // 行ごとにロックや書き込みをしないように、標準出力は1回だけロックしてバッファする
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for (file_num, filename) in config.files.iter().enumerate() {
+        match open(filename, config.skip_bytes, config.buffer_size) {
+            Err(err) => {
+                // エラーメッセージが前のファイルの出力より先に出ないようにする
+                stdout.flush()?;
+                eprintln!("Failed to open {}: {}", filename, err);
+                failed = true;
+            }
             Ok(reader) => {
-                let mut i = 1;
-                for line in reader.lines() {
-                    let l = line.unwrap();
-                    if config.number_lines || (config.number_nonblank_lines && !l.is_empty()) {
-                        println!("{:>6}\t{}", i, l);
+                if config.headers {
+                    writeln!(
+                        stdout,
+                        "{}==> {} <==",
+                        if file_num > 0 { "\n" } else { "" },
+                        filename
+                    )?;
+                }
+                // -vで制御文字も扱えるように、行をバイト列のまま読む。
+                // 逆順の場合は表示する順番で行番号を振る（tac | cat -n と同じ）
+                let lines: Box<dyn Iterator<Item = io::Result<Vec<u8>>>> = if config.reverse {
+                    Box::new(reader.split(b'\n').collect::<Vec<_>>().into_iter().rev())
+                } else {
+                    Box::new(reader.split(b'\n'))
+                };
+
+                // --matchで表示しない行も行番号は進める
+                for line in lines {
+                    let l = line?;
+                    let is_match = config
+                        .pattern
+                        .as_ref()
+                        .is_none_or(|re| re.is_match(&String::from_utf8_lossy(&l)));
+                    let numbered =
+                        config.number_lines || (config.number_nonblank_lines && !l.is_empty());
+                    if is_match {
+                        if numbered {
+                            write!(stdout, "{:>6}\t", i)?;
+                        }
+                        stdout.write_all(&show_line(
+                            &l,
+                            config.show_nonprinting,
+                            config.show_tabs,
+                            config.show_ends,
+                        ))?;
+                        stdout.write_all(b"\n")?;
+                    }
+                    if numbered {
                         i += 1;
-                    } else {
-                        println!("{}", l);
                     }
                 }
             }
         }
     }
-    Ok(())
+    stdout.flush()?;
+    Ok(!failed)
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -63,18 +114,213 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(false)
                 .required(false),
         )
+        .arg(
+            Arg::with_name("headers")
+                .long("headers")
+                .help("ファイルごとに「==> ファイル名 <==」のヘッダーを表示するかどうか")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .long("reverse")
+                .visible_alias("tac")
+                .help("ファイルごとに最後の行から逆順に表示するかどうか")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("skip_bytes")
+                .long("skip-bytes")
+                .value_name("BYTES")
+                .help("ファイルごとに先頭から読み飛ばすバイト数")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("pattern")
+                .long("match")
+                .value_name("PATTERN")
+                .help("正規表現にマッチする行だけを表示する"),
+        )
+        .arg(
+            Arg::with_name("buffer_size")
+                .long("buffer-size")
+                .visible_alias("chunk-size")
+                .value_name("BYTES")
+                .help("読み込みに使うバッファのバイト数")
+                .default_value("8192"),
+        )
+        .arg(
+            Arg::with_name("show_nonprinting")
+                .short("v")
+                .long("show-nonprinting")
+                .help("タブ以外の制御文字を^やM-の表記で表示する")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_tabs")
+                .short("T")
+                .long("show-tabs")
+                .help("タブを^Iと表示する")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_ends")
+                .short("E")
+                .long("show-ends")
+                .help("行末に$を表示する")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_all")
+                .short("A")
+                .long("show-all")
+                .help("-vETと同じ")
+                .takes_value(false),
+        )
         .get_matches();
 
+    let show_all = matches.is_present("show_all");
+
+    let skip_bytes = matches
+        .value_of("skip_bytes")
+        .map(|val| {
+            val.parse()
+                .map_err(|_| format!("illegal byte count -- {}", val))
+        })
+        .transpose()?;
+
+    let pattern = matches
+        .value_of("pattern")
+        .map(|val| Regex::new(val).map_err(|_| format!("Invalid pattern \"{}\"", val)))
+        .transpose()?;
+
+    let buffer_size = matches
+        .value_of("buffer_size")
+        .map(|val| match val.parse() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("invalid buffer size -- {}", val)),
+        })
+        .transpose()?;
+
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
         number_lines: matches.is_present("number"),
         number_nonblank_lines: matches.is_present("number_nonblank"),
+        headers: matches.is_present("headers"),
+        reverse: matches.is_present("reverse"),
+        skip_bytes: skip_bytes.unwrap(),
+        pattern,
+        buffer_size: buffer_size.unwrap(),
+        show_nonprinting: show_all || matches.is_present("show_nonprinting"),
+        show_tabs: show_all || matches.is_present("show_tabs"),
+        show_ends: show_all || matches.is_present("show_ends"),
     })
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+/// GNU catの-v/-T/-Eと同じ表記に変換する。
+/// 制御文字は^X、DELは^?、0x80以上のバイトはM-に続けて下位7ビットの表記にする
+fn show_line(line: &[u8], show_nonprinting: bool, show_tabs: bool, show_ends: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(line.len());
+    for &byte in line {
+        if byte == b'\t' {
+            if show_tabs {
+                out.extend_from_slice(b"^I");
+            } else {
+                out.push(byte);
+            }
+        } else if show_nonprinting {
+            let mut c = byte;
+            if c >= 0x80 {
+                out.extend_from_slice(b"M-");
+                c -= 0x80;
+            }
+            match c {
+                0..=0x1f => out.extend_from_slice(&[b'^', c + 0x40]),
+                0x7f => out.extend_from_slice(b"^?"),
+                _ => out.push(c),
+            }
+        } else {
+            out.push(byte);
+        }
+    }
+    if show_ends {
+        out.push(b'$');
+    }
+    out
+}
+
+/// gzipで圧縮されたファイルは展開して読む。
+/// --skip-bytesは展開した後のバイト数として扱う
+fn open(filename: &str, skip_bytes: u64, buffer_size: usize) -> MyResult<Box<dyn BufRead>> {
     match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+        // 標準入力はシークできないので読み捨てる
+        "-" => {
+            let mut reader = gunzip(
+                BufReader::with_capacity(buffer_size, io::stdin()),
+                buffer_size,
+            )?;
+            io::copy(&mut reader.by_ref().take(skip_bytes), &mut io::sink())?;
+            Ok(reader)
+        }
+        _ => {
+            let mut file = BufReader::with_capacity(buffer_size, File::open(filename)?);
+            if is_gzip(&mut file)? {
+                // 圧縮されたデータの途中にはシークできないので読み捨てる
+                let mut reader = gunzip(file, buffer_size)?;
+                io::copy(&mut reader.by_ref().take(skip_bytes), &mut io::sink())?;
+                Ok(reader)
+            } else {
+                file.seek(SeekFrom::Start(skip_bytes))?;
+                Ok(Box::new(file))
+            }
+        }
+    }
+}
+
+/// 拡張子ではなく、先頭のマジックナンバー（1f 8b）でgzipかどうかを判定する
+fn is_gzip<R: BufRead>(reader: &mut R) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.starts_with(&[0x1f, 0x8b]))
+}
+
+/// gzipであれば展開するリーダーで包む。
+/// `cat a.gz b.gz > c.gz`のように連結されたファイルもすべて展開する
+fn gunzip<R: BufRead + 'static>(mut reader: R, buffer_size: usize) -> MyResult<Box<dyn BufRead>> {
+    if is_gzip(&mut reader)? {
+        Ok(Box::new(BufReader::with_capacity(
+            buffer_size,
+            MultiGzDecoder::new(reader),
+        )))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::show_line;
+
+    #[test]
+    fn test_show_line() {
+        let line = [0x01, 0x7f, 0x80];
+
+        // -vの場合は制御文字、DEL、0x80以上のバイトを表記に変換する
+        assert_eq!(show_line(&line, true, false, false), b"^A^?M-^@");
+        assert_eq!(
+            show_line(&[0x89, 0xe9, 0xff], true, false, false),
+            b"M-^IM-iM-^?"
+        );
+        assert_eq!(show_line(b"\r", true, false, false), b"^M");
+
+        // 指定しない場合はそのまま表示する
+        assert_eq!(show_line(&line, false, false, false), line);
+
+        // タブは-Tの場合だけ変換する
+        assert_eq!(show_line(b"a\tb", true, false, false), b"a\tb");
+        assert_eq!(show_line(b"a\tb", false, true, false), b"a^Ib");
+
+        // -Eの場合は行末に$を表示する
+        assert_eq!(show_line(b"a\x01", true, true, true), b"a^A$");
+        assert_eq!(show_line(b"", false, false, true), b"$");
     }
 }
@@ -1,6 +1,10 @@
 fn main() {
-    if let Err(e) = catr::get_args().and_then(catr::run) {
-        eprint!("{}", e);
-        std::process::exit(1);
+    match catr::get_args().and_then(catr::run) {
+        Err(e) => {
+            eprint!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(false) => std::process::exit(1),
+        Ok(true) => {}
     }
 }
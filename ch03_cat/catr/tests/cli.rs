@@ -10,6 +10,7 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const SPIDERS: &str = "tests/inputs/spiders.txt";
 const BUSTLE: &str = "tests/inputs/the-bustle.txt";
+const BUSTLE_GZ: &str = "tests/inputs/the-bustle.txt.gz";
 
 // --------------------------------------------------
 #[test]
@@ -46,11 +47,25 @@ fn skips_bad_file() -> Result<()> {
     Command::cargo_bin(PRG)?
         .arg(&bad)
         .assert()
-        .success()
+        .code(1)
         .stderr(predicate::str::is_match(expected)?);
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn skips_bad_file_among_others() -> Result<()> {
+    // 開けないファイルを飛ばしてほかのファイルを表示し、終了コードは1にする
+    let bad = gen_bad_file();
+    let expected = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .args([&bad, FOX])
+        .assert()
+        .code(1)
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
     let expected = fs::read_to_string(expected_file)?;
@@ -180,6 +195,52 @@ fn bustle_b() -> Result<()> {
     run(&["-b", BUSTLE], "tests/expected/the-bustle.txt.b.out")
 }
 
+// --------------------------------------------------
+#[test]
+fn bustle_gz() -> Result<()> {
+    // gzipで圧縮されたファイルは展開して表示する
+    run(&[BUSTLE_GZ], "tests/expected/the-bustle.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_gz_n() -> Result<()> {
+    run(&["-n", BUSTLE_GZ], "tests/expected/the-bustle.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_gz_stdin() -> Result<()> {
+    // 標準入力もマジックナンバーで判定して展開する
+    let expected = fs::read_to_string("tests/expected/the-bustle.txt.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .write_stdin(fs::read(BUSTLE_GZ)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn show_all() -> Result<()> {
+    // -Aは-vETと同じ
+    Command::cargo_bin(PRG)?
+        .arg("-A")
+        .write_stdin(b"a\tb\x01\xe9\n".to_vec())
+        .assert()
+        .success()
+        .stdout("a^Ib^AM-i$\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-v", "-n"])
+        .write_stdin(b"a\tb\x7f\n".to_vec())
+        .assert()
+        .success()
+        .stdout("     1\ta\tb^?\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn all() -> Result<()> {
@@ -192,8 +253,142 @@ fn all_n() -> Result<()> {
     run(&[FOX, SPIDERS, BUSTLE, "-n"], "tests/expected/all.n.out")
 }
 
+// --------------------------------------------------
+#[test]
+fn fox_spiders_n() -> Result<()> {
+    // 2つ目のファイルの行番号は1つ目の続きから振る
+    run(&["-n", FOX, SPIDERS], "tests/expected/fox_spiders.n.out")
+}
+
 // --------------------------------------------------
 #[test]
 fn all_b() -> Result<()> {
     run(&[FOX, SPIDERS, BUSTLE, "-b"], "tests/expected/all.b.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn fox_spiders_headers() -> Result<()> {
+    run(
+        &["--headers", FOX, SPIDERS],
+        "tests/expected/fox_spiders.headers.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_reverse() -> Result<()> {
+    run(
+        &["--reverse", SPIDERS],
+        "tests/expected/spiders.txt.reverse.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_reverse_n() -> Result<()> {
+    run(
+        &["--tac", "-n", SPIDERS],
+        "tests/expected/spiders.txt.reverse.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_skip_bytes_n() -> Result<()> {
+    // 行番号は最初に表示する行から振る
+    run(
+        &["--skip-bytes", "5", "-n", SPIDERS],
+        "tests/expected/spiders.txt.skip5.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_skip_bytes_stdin() -> Result<()> {
+    run_stdin(
+        SPIDERS,
+        &["--skip-bytes", "5", "-"],
+        "tests/expected/spiders.txt.skip5.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_match_n() -> Result<()> {
+    // マッチした行だけを元の行番号で表示する
+    run(
+        &["--match", "^The", "-n", BUSTLE],
+        "tests/expected/the-bustle.txt.match.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_match_b() -> Result<()> {
+    run(
+        &["--match", "^[AU]", "-b", BUSTLE],
+        "tests/expected/the-bustle.txt.match.b.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn buffer_size_stdin() -> Result<()> {
+    // バッファの大きさによらず出力は入力と同じになる
+    let input: String = (1..=2000)
+        .map(|i| format!("line {} of the buffer size test\n", i))
+        .collect();
+    for size in ["1", "7", "8192", "65536"] {
+        Command::cargo_bin(PRG)?
+            .args(["--buffer-size", size, "-"])
+            .write_stdin(input.as_str())
+            .assert()
+            .success()
+            .stdout(input.clone());
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn large_input_n() -> Result<()> {
+    // 出力をバッファしても、大きな入力の内容と順番はそのまま
+    let input: String = (1..=100_000)
+        .map(|i| {
+            if i % 10 == 0 {
+                "\n".to_string()
+            } else {
+                format!("line {}\n", i)
+            }
+        })
+        .collect();
+    let expected: String = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>6}\t{}\n", i + 1, line))
+        .collect();
+    Command::cargo_bin(PRG)?
+        .args(["-n", "-", FOX])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}{:>6}\t{}",
+            expected,
+            100_001,
+            fs::read_to_string(FOX)?
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_buffer_size() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--buffer-size", "0", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid buffer size -- 0"));
+    Ok(())
+}
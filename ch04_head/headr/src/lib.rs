@@ -1,15 +1,13 @@
 use clap::{App, Arg};
-use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
+use common::{open, parse_positive_int, MyResult};
+use std::io::{self, BufRead, Read, Write};
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
     lines: usize,
     bytes: Option<usize>,
+    zero_terminated: bool,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -43,6 +41,13 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(true)
                 .default_value("10"),
         )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline")
+                .takes_value(false),
+        )
         .get_matches();
 
     let lines = matches
@@ -71,94 +76,120 @@ pub fn get_args() -> MyResult<Config> {
         files: matches.values_of_lossy("files").unwrap(),
         lines: lines.unwrap(),
         bytes,
+        zero_terminated: matches.is_present("zero_terminated"),
     })
 }
 
-pub fn run(config: Config) -> MyResult<()> {
+/// 開けない、または読めないファイルがあればfalseを返す
+pub fn run(config: Config) -> MyResult<bool> {
     let files = config.files;
     let has_multple_files = files.len() > 1;
+    let mut failed = false;
 
     for (file_num, filename) in files.iter().enumerate() {
-        match open(&filename) {
-            Err(err) => eprintln!("{}: {}", filename, err),
+        match open(filename) {
+            Err(err) => {
+                eprintln!("{}: {}", filename, err);
+                failed = true;
+            }
             Ok(reader) => {
                 if has_multple_files {
                     if file_num > 0 {
-                        println!("");
+                        println!();
                     }
                     println!("==> {} <==", filename)
                 }
-                match config.bytes {
-                    Some(num) => {
-                        let _ = read_file_with_bytes(reader, num);
-                    }
-                    None => {
-                        let _ = read_lines(reader, config.lines);
-                    }
+                let res = match config.bytes {
+                    Some(num) => head_bytes(reader, num),
+                    None => head_lines(
+                        reader,
+                        config.lines,
+                        if config.zero_terminated { b'\0' } else { b'\n' },
+                    ),
+                }
+                .and_then(|content| Ok(io::stdout().write_all(&content)?));
+                if let Err(err) = res {
+                    eprintln!("{}: {}", filename, err);
+                    failed = true;
                 }
             }
         }
     }
 
-    Ok(())
-}
-
-fn parse_positive_int(val: &str) -> MyResult<usize> {
-    match val.parse() {
-        Ok(n) if n > 0 => Ok(n),
-        _ => Err(From::from(val)),
-    }
-}
-
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
-    }
+    Ok(!failed)
 }
 
-fn read_file_with_bytes(mut reader: Box<dyn BufRead>, bytes: usize) -> MyResult<()> {
-    let mut handle = reader.take(bytes as u64);
-    let mut buffer = vec![0; bytes];
-    let bytes_read = handle.read(&mut buffer)?;
-
-    print!("{}", String::from_utf8_lossy(&buffer[..bytes_read]));
-
-    Ok(())
+/// 先頭からbytesバイトを返す。ファイルが短い場合はすべてを返す。
+/// bytesバイトを読んだらそれ以上は読まないので、終わらない入力でも止まる
+fn head_bytes(reader: impl Read, bytes: usize) -> MyResult<Vec<u8>> {
+    // -cに大きな値を指定しても、先にその大きさの領域を確保しない
+    let mut buffer = Vec::new();
+    reader.take(bytes as u64).read_to_end(&mut buffer)?;
+    Ok(buffer)
 }
 
-fn read_lines(mut reader: Box<dyn BufRead>, line_num: usize) -> MyResult<()> {
-    let mut line = String::new();
+/// delimiterで区切られた先頭のline_num行を、区切り文字も含めてそのまま返す
+fn head_lines(mut reader: impl BufRead, line_num: usize, delimiter: u8) -> MyResult<Vec<u8>> {
+    let mut content = vec![];
     for _ in 0..line_num {
-        let bytes = reader.read_line(&mut line)?;
+        let bytes = reader.read_until(delimiter, &mut content)?;
         if bytes == 0 {
             break;
         }
-        print!("{}", line);
-        line.clear();
     }
 
-    Ok(())
+    Ok(content)
 }
 
-fn open_byte(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
-    }
+#[test]
+fn test_head_lines() {
+    use std::io::Cursor;
+
+    let text = "one\ntwo\r\nthree\n";
+    let res = head_lines(Cursor::new(text), 2, b'\n');
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), b"one\ntwo\r\n");
+
+    // 行数が足りない場合はすべてを返す
+    let res = head_lines(Cursor::new(text), 10, b'\n');
+    assert_eq!(res.unwrap(), text.as_bytes());
+
+    // 最後の行に改行がない場合もそのまま返す
+    let res = head_lines(Cursor::new("one\ntwo"), 3, b'\n');
+    assert_eq!(res.unwrap(), b"one\ntwo");
+
+    // 空の入力
+    let res = head_lines(Cursor::new(""), 1, b'\n');
+    assert_eq!(res.unwrap(), b"");
+
+    // NUL区切り
+    let res = head_lines(Cursor::new("a\nb\0c\0"), 1, b'\0');
+    assert_eq!(res.unwrap(), b"a\nb\0");
 }
 
 #[test]
-fn test_parse_positive_int() {
-    let res = parse_positive_int("3");
+fn test_head_bytes() {
+    use std::io::Cursor;
+
+    let res = head_bytes(Cursor::new("one\ntwo\n"), 5);
     assert!(res.is_ok());
-    assert_eq!(res.unwrap(), 3);
+    assert_eq!(res.unwrap(), b"one\nt");
+
+    // マルチバイト文字の途中で切れてもバイト列をそのまま返す
+    let res = head_bytes(Cursor::new("Ñ"), 1);
+    assert_eq!(res.unwrap(), [0xc3]);
+
+    // 入力が短い場合はすべてを返す
+    let res = head_bytes(Cursor::new("one"), 10);
+    assert_eq!(res.unwrap(), b"one");
 
-    let res = parse_positive_int("foo");
-    assert!(res.is_err());
-    assert_eq!(res.unwrap_err().to_string(), "foo".to_string());
+    // 必要なバイト数だけを読み、残りは読まない
+    let mut cursor = Cursor::new(vec![b'y'; 1 << 20]);
+    let res = head_bytes(&mut cursor, 10);
+    assert_eq!(res.unwrap(), b"yyyyyyyyyy");
+    assert_eq!(cursor.position(), 10);
 
-    let res = parse_positive_int("0");
-    assert!(res.is_err());
-    assert_eq!(res.unwrap_err().to_string(), "0".to_string());
+    // 入力よりはるかに大きな値でも読んだ分だけを返す
+    let res = head_bytes(Cursor::new("one"), usize::MAX);
+    assert_eq!(res.unwrap(), b"one");
 }
@@ -1,6 +1,10 @@
 fn main() {
-    if let Err(e) = headr::get_args().and_then(headr::run) {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    match headr::get_args().and_then(headr::run) {
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(false) => std::process::exit(1),
+        Ok(true) => {}
     }
 }
@@ -1,10 +1,12 @@
 use anyhow::Result;
-use assert_cmd::Command;
+use assert_cmd::{cargo::CommandCargoExt, Command};
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
 use std::fs::{self, File};
 use std::io::prelude::*;
+use std::process::Stdio;
+use std::thread;
 
 const PRG: &str = "headr";
 const EMPTY: &str = "./tests/inputs/empty.txt";
@@ -90,11 +92,52 @@ fn skips_bad_file() -> Result<()> {
     Command::cargo_bin(PRG)?
         .args([EMPTY, &bad, ONE])
         .assert()
+        .code(1)
         .stderr(predicate::str::is_match(expected)?);
 
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn bytes_endless_stdin() -> Result<()> {
+    // 終わらない入力でも、必要なバイト数を読んだら終了する
+    let mut child = std::process::Command::cargo_bin(PRG)?
+        .args(["-c", "10"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || while stdin.write_all(&[b'y'; 1024]).is_ok() {});
+
+    let output = child.wait_with_output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"yyyyyyyyyy");
+    writer.join().unwrap();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_terminated() -> Result<()> {
+    // -zの場合はNULを行の区切りとして扱う
+    Command::cargo_bin(PRG)?
+        .args(["-z", "-n", "2"])
+        .write_stdin("a\0b\0c\0")
+        .assert()
+        .success()
+        .stdout("a\0b\0");
+
+    // 改行は区切りにならない
+    Command::cargo_bin(PRG)?
+        .args(["--zero-terminated", "-n", "1"])
+        .write_stdin("a\nb\0c\0")
+        .assert()
+        .success()
+        .stdout("a\nb\0");
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
     // Extra work here due to lossy UTF
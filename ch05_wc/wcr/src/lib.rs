@@ -1,9 +1,8 @@
 use clap::{App, Arg};
-use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
+use common::{open, MyResult};
+use std::io::{BufRead, Read};
+use std::thread;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -12,6 +11,21 @@ pub struct Config {
     words: bool,
     bytes: bool,
     chars: bool,
+    unicode_words: bool,
+    jobs: usize,
+    expect_lines: Option<usize>,
+    expect_words: Option<usize>,
+    expect_bytes: Option<usize>,
+    total: TotalMode,
+}
+
+/// 合計の行をいつ表示するか
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TotalMode {
+    Auto,
+    Always,
+    Only,
+    Never,
 }
 
 #[derive(Debug, PartialEq)]
@@ -35,6 +49,13 @@ pub fn get_args() -> MyResult<Config> {
                 .multiple(true)
                 .default_value("-"),
         )
+        .arg(
+            Arg::with_name("files0_from")
+                .long("files0-from")
+                .value_name("F")
+                .help("Read input file names from NUL-separated list in F (- for stdin)")
+                .conflicts_with("files"),
+        )
         .arg(
             Arg::with_name("lines")
                 .short("l")
@@ -68,6 +89,44 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(false)
                 .required(false),
         )
+        .arg(
+            Arg::with_name("unicode_words")
+                .long("unicode-words")
+                .help("Count words using Unicode word boundaries"),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .long("jobs")
+                .value_name("N")
+                .help("Count N files in parallel")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("expect_lines")
+                .long("expect-lines")
+                .value_name("N")
+                .help("Fail unless each file has N lines"),
+        )
+        .arg(
+            Arg::with_name("expect_words")
+                .long("expect-words")
+                .value_name("N")
+                .help("Fail unless each file has N words"),
+        )
+        .arg(
+            Arg::with_name("expect_bytes")
+                .long("expect-bytes")
+                .value_name("N")
+                .help("Fail unless each file has N bytes"),
+        )
+        .arg(
+            Arg::with_name("total")
+                .long("total")
+                .value_name("WHEN")
+                .help("When to print a line with total counts")
+                .possible_values(&["auto", "always", "only", "never"])
+                .default_value("auto"),
+        )
         .get_matches();
 
     let mut lines = matches.is_present("lines");
@@ -81,12 +140,49 @@ pub fn get_args() -> MyResult<Config> {
         bytes = true
     }
 
+    let jobs = matches
+        .value_of("jobs")
+        .map(|val| match val.parse() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("invalid number of jobs: '{}'", val)),
+        })
+        .transpose()?
+        .unwrap();
+
+    let expect = |name: &str| {
+        matches
+            .value_of(name)
+            .map(|val| {
+                val.parse()
+                    .map_err(|_| format!("invalid expected count: '{}'", val))
+            })
+            .transpose()
+    };
+
+    let files = match matches.value_of("files0_from") {
+        Some(list) => read_files0(list)?,
+        None => matches.values_of_lossy("files").unwrap(),
+    };
+
+    let total = match matches.value_of("total") {
+        Some("always") => TotalMode::Always,
+        Some("only") => TotalMode::Only,
+        Some("never") => TotalMode::Never,
+        _ => TotalMode::Auto,
+    };
+
     Ok(Config {
-        files: matches.values_of_lossy("files").unwrap(),
+        files,
         lines,
         words,
         bytes,
         chars,
+        unicode_words: matches.is_present("unicode_words"),
+        jobs,
+        expect_lines: expect("expect_lines")?,
+        expect_words: expect("expect_words")?,
+        expect_bytes: expect("expect_bytes")?,
+        total,
     })
 }
 
@@ -95,52 +191,135 @@ pub fn run(config: Config) -> MyResult<()> {
     let mut total_words = 0;
     let mut total_bytes = 0;
     let mut total_chars = 0;
+    let mut mismatches = vec![];
 
-    for filename in &config.files {
-        match open(filename) {
-            Err(err) => eprint!("Failed to open {}: {}", filename, err),
-            Ok(file) => {
-                if let Ok(info) = count(file) {
+    let results = count_files(&config.files, config.jobs, config.unicode_words);
+    for (filename, result) in config.files.iter().zip(results) {
+        match result {
+            Err(err) => eprintln!("{}", err),
+            Ok(info) => {
+                if config.total != TotalMode::Only {
                     println!(
                         "{}{}{}{}{}",
                         format_field(info.num_lines, config.lines),
                         format_field(info.num_words, config.words),
                         format_field(info.num_bytes, config.bytes),
                         format_field(info.num_chars, config.chars),
-                        if filename.as_str() == "-" {
+                        // 標準入力だけを数える場合は名前を表示しない
+                        if filename.as_str() == "-" && config.files.len() == 1 {
                             "".to_string()
                         } else {
                             format!(" {}", filename)
                         }
                     );
+                }
+
+                total_lines += info.num_lines;
+                total_words += info.num_words;
+                total_bytes += info.num_bytes;
+                total_chars += info.num_chars;
 
-                    total_lines += info.num_lines;
-                    total_words += info.num_words;
-                    total_bytes += info.num_bytes;
-                    total_chars += info.num_chars;
+                for (expected, actual, unit) in [
+                    (config.expect_lines, info.num_lines, "lines"),
+                    (config.expect_words, info.num_words, "words"),
+                    (config.expect_bytes, info.num_bytes, "bytes"),
+                ] {
+                    if let Some(expected) = expected.filter(|&n| n != actual) {
+                        mismatches.push(format!(
+                            "{}: expected {} {}, got {}",
+                            filename, expected, unit, actual
+                        ));
+                    }
                 }
             }
         }
     }
 
-    if config.files.len() > 1 {
+    let show_total = match config.total {
+        TotalMode::Auto => config.files.len() > 1,
+        TotalMode::Always | TotalMode::Only => true,
+        TotalMode::Never => false,
+    };
+    if show_total {
         println!(
-            "{}{}{}{} total",
+            "{}{}{}{}{}",
             format_field(total_lines, config.lines),
             format_field(total_words, config.words),
             format_field(total_bytes, config.bytes),
             format_field(total_chars, config.chars),
+            // 合計だけを表示する場合はGNU wcと同じく「total」を付けない
+            if config.total == TotalMode::Only {
+                ""
+            } else {
+                " total"
+            }
         )
     }
 
+    // 期待した値と違う場合はまとめてエラーにする
+    if !mismatches.is_empty() {
+        return Err(From::from(mismatches.join("\n")));
+    }
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+/// NUL区切りのファイル名の一覧を読む。空の名前は無視する
+fn read_files0(list: &str) -> MyResult<Vec<String>> {
+    let mut buf = vec![];
+    open(list)
+        .and_then(|mut reader| Ok(reader.read_to_end(&mut buf)?))
+        .map_err(|e| format!("cannot open '{}' for reading: {}", list, e))?;
+
+    Ok(buf
+        .split(|&b| b == b'\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).to_string())
+        .collect())
+}
+
+fn count_file(filename: &str, unicode_words: bool) -> Result<FileInfo, String> {
+    let file = open(filename).map_err(|e| format!("Failed to open {}: {}", filename, e))?;
+    count(file, unicode_words).map_err(|e| format!("{}: {}", filename, e))
+}
+
+/// ファイルごとの結果を入力と同じ順番で返す。
+/// jobsが2以上の場合はjobs個のスレッドでファイルを分担して数える
+fn count_files(
+    files: &[String],
+    jobs: usize,
+    unicode_words: bool,
+) -> Vec<Result<FileInfo, String>> {
+    if jobs <= 1 || files.len() <= 1 {
+        return files
+            .iter()
+            .map(|filename| count_file(filename, unicode_words))
+            .collect();
     }
+
+    let mut results: Vec<Option<Result<FileInfo, String>>> = files.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..jobs)
+            .map(|worker| {
+                scope.spawn(move || {
+                    files
+                        .iter()
+                        .enumerate()
+                        .skip(worker)
+                        .step_by(jobs)
+                        .map(|(i, filename)| (i, count_file(filename, unicode_words)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            for (i, result) in handle.join().unwrap() {
+                results[i] = Some(result);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
 }
 
 fn format_field(value: usize, show: bool) -> String {
@@ -151,7 +330,8 @@ fn format_field(value: usize, show: bool) -> String {
     }
 }
 
-pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
+/// unicode_wordsがtrueの場合は、空白ではなくUnicodeの単語境界で単語を数える
+pub fn count(mut file: impl BufRead, unicode_words: bool) -> MyResult<FileInfo> {
     let mut num_lines = 0;
     let mut num_words = 0;
     let mut num_bytes = 0;
@@ -166,7 +346,11 @@ pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
 
         num_bytes += line_bytes;
         num_lines += 1;
-        num_words += line.split_whitespace().count();
+        num_words += if unicode_words {
+            line.unicode_words().count()
+        } else {
+            line.split_whitespace().count()
+        };
         num_chars += line.chars().count();
         line.clear();
     }
@@ -181,7 +365,7 @@ pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
 
 #[cfg(test)]
 mod tests {
-    use super::{count, FileInfo};
+    use super::{count, count_files, FileInfo};
     use std::io::Cursor;
 
     #[test]
@@ -194,9 +378,45 @@ mod tests {
             num_bytes: 48,
         };
 
-        let info = count(Cursor::new(text));
+        let info = count(Cursor::new(text), false);
 
         assert!(info.is_ok());
         assert_eq!(info.unwrap(), expected)
     }
+
+    #[test]
+    fn test_count_unicode_words() {
+        // 全角スペース（U+3000）で区切られた2つの単語
+        let text = "hello\u{3000}world\n";
+        let info = count(Cursor::new(text), true);
+        assert!(info.is_ok());
+        assert_eq!(info.unwrap().num_words, 2);
+
+        // 単語境界で数える場合、記号だけの並びは単語に含めない
+        let text = "-- fox --\n";
+        assert_eq!(count(Cursor::new(text), false).unwrap().num_words, 3);
+        assert_eq!(count(Cursor::new(text), true).unwrap().num_words, 1);
+    }
+
+    #[test]
+    fn test_count_files_parallel() {
+        let files: Vec<String> = [
+            "tests/inputs/fox.txt",
+            "tests/inputs/atlamal.txt",
+            "tests/inputs/empty.txt",
+            "tests/inputs/fox.txt",
+            "no-such-file",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        // 並列で数えた結果は順番も含めて逐次の結果と一致するはず
+        let sequential = count_files(&files, 1, false);
+        for jobs in [2, 3, 8] {
+            assert_eq!(count_files(&files, jobs, false), sequential);
+        }
+        assert_eq!(sequential[0].as_ref().unwrap().num_words, 9);
+        assert!(sequential[4].is_err());
+    }
 }
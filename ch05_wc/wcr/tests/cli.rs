@@ -9,6 +9,7 @@ const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const FILES0: &str = "tests/inputs/files0.txt";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -176,6 +177,21 @@ fn atlamal_stdin() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn fox_stdin_atlamal() -> Result<()> {
+    // ファイルの間にある「-」で標準入力を読み、合計にも含める
+    let input = fs::read_to_string(FOX)?;
+    let expected = fs::read_to_string("tests/expected/fox_stdin_atlamal.out")?;
+    Command::cargo_bin(PRG)?
+        .args([FOX, "-", ATLAMAL])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn test_all() -> Result<()> {
@@ -217,3 +233,118 @@ fn test_all_words_lines() -> Result<()> {
 fn test_all_bytes_lines() -> Result<()> {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn test_all_jobs() -> Result<()> {
+    run(
+        &["--jobs", "3", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_expect_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--expect-lines", "1", "--expect-words", "9", FOX])
+        .assert()
+        .success();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_fox_expect_lines() -> Result<()> {
+    // 数えた結果は表示したうえで、違いをエラーとして報告する
+    Command::cargo_bin(PRG)?
+        .args(["--expect-lines", "2", "--expect-bytes", "48", FOX])
+        .assert()
+        .code(1)
+        .stdout("       1       9      48 tests/inputs/fox.txt\n")
+        .stderr("tests/inputs/fox.txt: expected 2 lines, got 1\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> Result<()> {
+    // NUL区切りの一覧にあるファイルを数え、合計も表示する
+    run(&["--files0-from", FILES0], "tests/expected/files0.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_stdin() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/files0.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", "-"])
+        .write_stdin(fs::read(FILES0)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_and_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", FILES0, FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_modes() -> Result<()> {
+    let all = fs::read_to_string("tests/expected/all.l.out")?;
+    let (files, total) = all.rsplit_once("       5").unwrap();
+    assert_eq!(total, " total\n");
+
+    // autoは2つ以上のファイルを数えたときだけ合計を表示する
+    for (args, expected) in [
+        (vec!["--total", "auto"], all.clone()),
+        (vec!["--total=always"], all.clone()),
+        (vec!["--total", "never"], files.to_string()),
+        (vec!["--total=only"], "       5\n".to_string()),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .args(["-l", EMPTY, FOX, ATLAMAL])
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_always_single_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-w", "--total", "always", FOX])
+        .assert()
+        .success()
+        .stdout("       9 tests/inputs/fox.txt\n       9 total\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-w", "--total", "only", FOX])
+        .assert()
+        .success()
+        .stdout("       9\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_total() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--total", "sometimes", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("isn't a valid value"));
+    Ok(())
+}
@@ -1,12 +1,10 @@
 use clap::{App, Arg};
+use common::{open, MyResult};
 use std::{
-    error::Error,
     fs::File,
-    io::{self, BufRead, BufReader, Write},
+    io::{self, BufRead, Write},
 };
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
 #[derive(Debug)]
 pub struct Config {
     in_file: String,
@@ -49,23 +47,29 @@ pub fn get_args() -> MyResult<Config> {
 }
 
 pub fn run(config: Config) -> MyResult<()> {
-    let mut file = open(&config.in_file).map_err(|e| format!("{}: {}", config.in_file, e))?;
+    let file = open(&config.in_file).map_err(|e| format!("{}: {}", config.in_file, e))?;
 
-    let mut out_file: Box<dyn Write> = match &config.out_file {
+    let out_file: Box<dyn Write> = match &config.out_file {
         Some(out_name) => Box::new(File::create(out_name)?),
         _ => Box::new(io::stdout()),
     };
 
+    uniq(file, out_file, config.count)
+}
+
+/// 連続する同じ行を1行にまとめてoutに書く。
+/// まとまりが終わるたびに書き出すので、保持するのは直前の行だけ
+fn uniq(mut file: impl BufRead, mut out: impl Write, show_count: bool) -> MyResult<()> {
     let mut line = String::new();
     let mut previous = String::new();
     let mut count: u64 = 0;
 
     let mut print = |count: u64, text: &str| -> MyResult<()> {
         if count > 0 {
-            if config.count {
-                write!(out_file, "{:>4} {}", count, text)?;
+            if show_count {
+                write!(out, "{:>4} {}", count, text)?;
             } else {
-                write!(out_file, "{}", text)?;
+                write!(out, "{}", text)?;
             }
         };
 
@@ -92,9 +96,37 @@ pub fn run(config: Config) -> MyResult<()> {
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+#[cfg(test)]
+mod tests {
+    use super::uniq;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_uniq() {
+        let mut out = vec![];
+        let res = uniq(Cursor::new("a\na\nb\na\r\nc"), &mut out, true);
+        assert!(res.is_ok());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "   2 a\n   1 b\n   1 a\r\n   1 c"
+        );
+    }
+
+    #[test]
+    fn test_uniq_large() {
+        // 重複の少ない大きな入力でも、まとまりごとに順番どおり書き出す
+        let input: String = (0..100_000).map(|i| format!("line {}\n", i / 3)).collect();
+        let expected: String = (0..33_334).map(|i| format!("line {}\n", i)).collect();
+        let expected_count: String = (0..33_334)
+            .map(|i| format!("{:>4} line {}\n", if i < 33_333 { 3 } else { 1 }, i))
+            .collect();
+
+        let mut out = vec![];
+        assert!(uniq(Cursor::new(&input), &mut out, false).is_ok());
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let mut out = vec![];
+        assert!(uniq(Cursor::new(&input), &mut out, true).is_ok());
+        assert_eq!(String::from_utf8(out).unwrap(), expected_count);
     }
 }
@@ -1,9 +1,19 @@
 use crate::EntryType::*;
 use clap::{App, Arg};
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
 use std::error::Error;
+use std::fs::Metadata;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
 use walkdir::{DirEntry, WalkDir};
 
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -13,11 +23,34 @@ enum EntryType {
     Link,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimeField {
+    Accessed,
+    Modified,
+    Changed,
+}
+
+/// -mtimeや-mminのような時刻の条件。
+/// 経過時間をunit秒単位で切り捨てた値をamountと比べる
+#[derive(Debug, PartialEq)]
+struct TimeTest {
+    field: TimeField,
+    unit: i64,
+    sign: Option<char>,
+    amount: i64,
+}
+
 #[derive(Debug)]
 pub struct Config {
     paths: Vec<String>,
     names: Vec<Regex>,
     entry_types: Vec<EntryType>,
+    path_patterns: Vec<Regex>,
+    regexes: Vec<Regex>,
+    time_tests: Vec<TimeTest>,
+    one_filesystem: bool,
+    sort: bool,
+    print0: bool,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -51,6 +84,83 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(true)
                 .multiple(true),
         )
+        .arg(
+            Arg::with_name("path_patterns")
+                .value_name("PATTERN")
+                .long("path")
+                .help("Path matches PATTERN")
+                .takes_value(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("ipath_patterns")
+                .value_name("PATTERN")
+                .long("ipath")
+                .help("Like --path, but the match is case-insensitive")
+                .takes_value(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("regexes")
+                .value_name("PATTERN")
+                .long("regex")
+                .help("Whole path matches PATTERN")
+                .takes_value(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("iregexes")
+                .value_name("PATTERN")
+                .long("iregex")
+                .help("Like --regex, but the match is case-insensitive")
+                .takes_value(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("mtime")
+                .value_name("N")
+                .long("mtime")
+                .help("Modified N*24 hours ago (+N: more, -N: less)")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("mmin")
+                .value_name("N")
+                .long("mmin")
+                .help("Modified N minutes ago (+N: more, -N: less)")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("amin")
+                .value_name("N")
+                .long("amin")
+                .help("Accessed N minutes ago (+N: more, -N: less)")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("cmin")
+                .value_name("N")
+                .long("cmin")
+                .help("Status changed N minutes ago (+N: more, -N: less)")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("one_filesystem")
+                .long("xdev")
+                .visible_alias("mount")
+                .help("Don't descend directories on other filesystems"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .long("sort")
+                .help("Sort entries by path"),
+        )
+        .arg(
+            Arg::with_name("print0")
+                .long("print0")
+                .visible_alias("null")
+                .help("Separate entries with NUL instead of newline"),
+        )
         .get_matches();
 
     let names = matches
@@ -78,15 +188,146 @@ pub fn get_args() -> MyResult<Config> {
         })
         .unwrap_or_default();
 
+    // --regexはパス全体にマッチさせる
+    let path_patterns = [
+        parse_patterns(
+            matches.values_of_lossy("path_patterns"),
+            "--path",
+            false,
+            false,
+        )?,
+        parse_patterns(
+            matches.values_of_lossy("ipath_patterns"),
+            "--ipath",
+            true,
+            false,
+        )?,
+    ]
+    .concat();
+    let regexes = [
+        parse_patterns(matches.values_of_lossy("regexes"), "--regex", false, true)?,
+        parse_patterns(matches.values_of_lossy("iregexes"), "--iregex", true, true)?,
+    ]
+    .concat();
+
+    let mut time_tests = vec![];
+    for (name, field, unit) in [
+        ("mtime", TimeField::Modified, 24 * 60 * 60),
+        ("mmin", TimeField::Modified, 60),
+        ("amin", TimeField::Accessed, 60),
+        ("cmin", TimeField::Changed, 60),
+    ] {
+        if let Some(val) = matches.value_of(name) {
+            // 状態の変更時刻（ctime）はUnixにしかない
+            if cfg!(not(unix)) && field == TimeField::Changed {
+                return Err(From::from(format!(
+                    "--{} is not supported on this platform",
+                    name
+                )));
+            }
+            let test = parse_time_test(val, field, unit)
+                .ok_or_else(|| format!("Invalid --{} '{}'", name, val))?;
+            time_tests.push(test);
+        }
+    }
+
     Ok(Config {
         paths: matches.values_of_lossy("path").unwrap(),
         names,
         entry_types,
+        path_patterns,
+        regexes,
+        time_tests,
+        one_filesystem: matches.is_present("one_filesystem"),
+        sort: matches.is_present("sort"),
+        print0: matches.is_present("print0"),
     })
 }
 
-pub fn run(config: Config) -> MyResult<()> {
+fn parse_patterns(
+    vals: Option<Vec<String>>,
+    opt: &str,
+    insensitive: bool,
+    whole: bool,
+) -> MyResult<Vec<Regex>> {
+    vals.unwrap_or_default()
+        .iter()
+        .map(|val| {
+            let pattern = if whole {
+                format!("^(?:{})$", val)
+            } else {
+                val.to_string()
+            };
+            RegexBuilder::new(&pattern)
+                .case_insensitive(insensitive)
+                .build()
+                .map_err(|_e| From::from(format!("Invalid {} '{}'", opt, val)))
+        })
+        .collect()
+}
+
+/// 1970年1月1日からの秒数。それより前の時刻は負の値に切り捨てる
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(e) => {
+            let before = e.duration();
+            -(before.as_secs() as i64) - (before.subsec_nanos() > 0) as i64
+        }
+    }
+}
+
+#[cfg(unix)]
+fn changed_secs(metadata: &Metadata) -> Option<i64> {
+    Some(metadata.ctime())
+}
+
+#[cfg(not(unix))]
+fn changed_secs(_metadata: &Metadata) -> Option<i64> {
+    None
+}
+
+/// 「+N」「-N」「N」の形式の値を解釈する
+fn parse_time_test(val: &str, field: TimeField, unit: i64) -> Option<TimeTest> {
+    let (sign, num) = match val.chars().next() {
+        Some(c @ ('+' | '-')) => (Some(c), &val[1..]),
+        _ => (None, val),
+    };
+    if num.is_empty() || !num.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(TimeTest {
+        field,
+        unit,
+        sign,
+        amount: num.parse().ok()?,
+    })
+}
 
+impl TimeTest {
+    /// 時刻を読めない場合はマッチしないものとする
+    fn is_match(&self, metadata: &Metadata, now: i64) -> bool {
+        let timestamp = match self.field {
+            TimeField::Accessed => metadata.accessed().ok().map(unix_secs),
+            TimeField::Modified => metadata.modified().ok().map(unix_secs),
+            TimeField::Changed => changed_secs(metadata),
+        };
+        timestamp.is_some_and(|timestamp| self.is_match_at(timestamp, now))
+    }
+
+    fn is_match_at(&self, timestamp: i64, now: i64) -> bool {
+        let elapsed = (now - timestamp).div_euclid(self.unit);
+        match self.sign {
+            Some('+') => elapsed > self.amount,
+            Some('-') => elapsed < self.amount,
+            _ => elapsed == self.amount,
+        }
+    }
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let now = unix_secs(SystemTime::now());
     let type_fileter = |entry: &DirEntry| {
         config.entry_types.is_empty()
             || config
@@ -107,8 +348,26 @@ pub fn run(config: Config) -> MyResult<()> {
                 .any(|re| re.is_match(&entry.file_name().to_string_lossy()))
     };
 
+    let path_fileter = |entry: &DirEntry| {
+        let path = entry.path().to_string_lossy();
+        (config.path_patterns.is_empty()
+            || config.path_patterns.iter().any(|re| re.is_match(&path)))
+            && (config.regexes.is_empty() || config.regexes.iter().any(|re| re.is_match(&path)))
+    };
+
+    let time_fileter = |entry: &DirEntry| {
+        config.time_tests.is_empty()
+            || entry.metadata().is_ok_and(|metadata| {
+                config
+                    .time_tests
+                    .iter()
+                    .all(|test| test.is_match(&metadata, now))
+            })
+    };
+
     for path in config.paths {
-        let entries = WalkDir::new(path)
+        let mut entries = WalkDir::new(path)
+            .same_file_system(config.one_filesystem)
             .into_iter()
             .filter_map(|e| match e {
                 Err(e) => {
@@ -119,11 +378,121 @@ pub fn run(config: Config) -> MyResult<()> {
             })
             .filter(type_fileter)
             .filter(name_fileter)
-            .map(|entry| entry.path().display().to_string())
+            .filter(path_fileter)
+            .filter(time_fileter)
+            .map(|entry| entry.into_path())
             .collect::<Vec<_>>();
 
-        println!("{}", entries.join("\n"));
+        if config.sort {
+            entries.sort();
+        }
+
+        let terminator = if config.print0 { b'\0' } else { b'\n' };
+        let mut out = io::stdout().lock();
+        for entry in &entries {
+            out.write_all(&path_bytes(entry))?;
+            out.write_all(&[terminator])?;
+        }
     }
 
     Ok(())
 }
+
+/// UnixではUTF-8でないパスもそのまま出力できるように、バイト列のまま返す
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+/// Unix以外ではパスをバイト列として取り出せないので、UTF-8に変換する
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Owned(path.to_string_lossy().into_owned().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_time_test, unix_secs, TimeField, TimeTest};
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_parse_time_test() {
+        let mmin = |val| parse_time_test(val, TimeField::Modified, 60);
+        assert_eq!(
+            mmin("+5"),
+            Some(TimeTest {
+                field: TimeField::Modified,
+                unit: 60,
+                sign: Some('+'),
+                amount: 5,
+            })
+        );
+        assert_eq!(mmin("-5").unwrap().sign, Some('-'));
+        assert_eq!(mmin("5").unwrap().sign, None);
+
+        // 数値でなければエラー
+        assert_eq!(mmin(""), None);
+        assert_eq!(mmin("+"), None);
+        assert_eq!(mmin("5m"), None);
+        assert_eq!(mmin("--5"), None);
+    }
+
+    #[test]
+    fn test_time_test_minutes() {
+        // 10分30秒前に更新したファイルを用意する
+        let file = NamedTempFile::new().unwrap();
+        let now = SystemTime::now();
+        let modified = now - Duration::from_secs(10 * 60 + 30);
+        File::options()
+            .write(true)
+            .open(file.path())
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let metadata = file.path().metadata().unwrap();
+        let now = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        // 経過時間は分単位で切り捨てて比べる
+        let mmin = |val| parse_time_test(val, TimeField::Modified, 60).unwrap();
+        assert!(mmin("10").is_match(&metadata, now));
+        assert!(!mmin("11").is_match(&metadata, now));
+        assert!(mmin("+9").is_match(&metadata, now));
+        assert!(!mmin("+10").is_match(&metadata, now));
+        assert!(mmin("-11").is_match(&metadata, now));
+        assert!(!mmin("-10").is_match(&metadata, now));
+
+        // 日単位では0日前になる
+        let mtime = |val| parse_time_test(val, TimeField::Modified, 24 * 60 * 60).unwrap();
+        assert!(mtime("0").is_match(&metadata, now));
+        assert!(mtime("-1").is_match(&metadata, now));
+        assert!(!mtime("+0").is_match(&metadata, now));
+    }
+
+    #[test]
+    fn test_unix_secs() {
+        let epoch = SystemTime::UNIX_EPOCH;
+        assert_eq!(unix_secs(epoch), 0);
+        assert_eq!(unix_secs(epoch + Duration::from_millis(1500)), 1);
+
+        // 1970年より前は負の方向に切り捨てる
+        assert_eq!(unix_secs(epoch - Duration::from_secs(60)), -60);
+        assert_eq!(unix_secs(epoch - Duration::from_millis(1500)), -2);
+    }
+
+    #[test]
+    fn test_time_test_fields() {
+        let now = 1_000_000;
+        let amin = parse_time_test("-2", TimeField::Accessed, 60).unwrap();
+        assert!(amin.is_match_at(now - 119, now));
+        assert!(!amin.is_match_at(now - 120, now));
+
+        // 未来の時刻はマイナスの経過時間として扱う
+        let cmin = parse_time_test("-0", TimeField::Changed, 60).unwrap();
+        assert!(cmin.is_match_at(now + 30, now));
+    }
+}
@@ -60,14 +60,14 @@ fn dies_bad_type() -> Result<()> {
 
 // --------------------------------------------------
 #[cfg(windows)]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Owned(format!("{}.windows", expected_file))
     format!("{}.windows", expected_file).into()
 }
 
 // --------------------------------------------------
 #[cfg(not(windows))]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Borrowed(expected_file)
     expected_file.into()
 }
@@ -96,6 +96,59 @@ fn path1() -> Result<()> {
     run(&["tests/inputs"], "tests/expected/path1.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn path1_xdev() -> Result<()> {
+    // tests/inputsは1つのファイルシステム上にあるので結果は変わらない
+    run(&["tests/inputs", "--xdev"], "tests/expected/path1.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn type_f_mount() -> Result<()> {
+    run(
+        &["tests/inputs", "--mount", "-t", "f"],
+        "tests/expected/type_f.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn path1_sort_print0() -> Result<()> {
+    // ソートした結果もNULで区切って表示する
+    let file = format_file_name("tests/expected/path1.txt");
+    let contents = fs::read_to_string(file.as_ref())?;
+    let mut paths: Vec<&str> = contents.split('\n').filter(|s| !s.is_empty()).collect();
+    paths.sort();
+    let expected: String = paths.iter().map(|path| format!("{}\0", path)).collect();
+
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--sort", "--print0"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn print0_space_in_name() -> Result<()> {
+    // 空白や改行を含む名前もNULで区切ればそのまま受け渡せる
+    let dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("a b.txt"), "")?;
+    fs::write(dir.path().join("c\nd.txt"), "")?;
+
+    let root = dir.path().display().to_string();
+    let expected = format!("{root}\0{root}/a b.txt\0{root}/c\nd.txt\0");
+    Command::cargo_bin(PRG)?
+        .args([&root, "--sort", "--null"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn path_a() -> Result<()> {
@@ -309,3 +362,81 @@ fn unreadable_dir() -> Result<()> {
     assert!(stderr.contains("cant-touch-this: Permission denied"));
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn path_upper() -> Result<()> {
+    run(
+        &["tests/inputs/a/b", "--path", "A/B"],
+        "tests/expected/path_a_b_upper.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn ipath_upper() -> Result<()> {
+    run(
+        &["tests/inputs/a/b", "--ipath", "A/B"],
+        "tests/expected/ipath_a_b.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn iregex_upper() -> Result<()> {
+    // --regexと同様にパス全体にマッチする必要がある
+    run(
+        &["tests/inputs", "--iregex", r".*D\.TXT", "--iregex", "D"],
+        "tests/expected/iregex_d_txt.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(not(windows))]
+fn path_nested_dir() -> Result<()> {
+    // ファイル名ではなくパスの途中のディレクトリ名で絞り込む
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("dir/sub"))?;
+    fs::create_dir_all(root.join("other/dir.txt"))?;
+    for file in [
+        "dir/a.txt",
+        "dir/b.csv",
+        "dir/sub/c.txt",
+        "other/d.txt",
+        "e.txt",
+    ] {
+        fs::write(root.join(file), "")?;
+    }
+
+    let root = root.display().to_string();
+    Command::cargo_bin(PRG)?
+        .args([&root, "--sort", "--path", r".*/dir/.*\.txt"])
+        .assert()
+        .success()
+        .stdout(format!("{root}/dir/a.txt\n{root}/dir/sub/c.txt\n"));
+
+    // -typeとはAND、--path同士や--ipathとはORになる
+    Command::cargo_bin(PRG)?
+        .args([&root, "--sort", "--type", "f"])
+        .args(["--ipath", r"/DIR/A\.TXT$", "--path", r"/other/"])
+        .assert()
+        .success()
+        .stdout(format!("{root}/dir/a.txt\n{root}/other/d.txt\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(not(unix))]
+fn dies_cmin_not_supported() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--cmin", "-5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--cmin is not supported on this platform",
+        ));
+    Ok(())
+}
@@ -1,16 +1,14 @@
 use crate::Extract::*;
 use clap::{App, Arg};
+use common::{open, MyResult};
 use csv::{ReaderBuilder, StringRecord, WriterBuilder};
 use regex::Regex;
 use std::{
-    error::Error,
-    fs::File,
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead},
     num::NonZeroUsize,
     ops::Range,
 };
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
 type PositionList = Vec<Range<usize>>;
 
 #[derive(Debug)]
@@ -25,6 +23,8 @@ pub struct Config {
     files: Vec<String>,
     delimiter: u8,
     extract: Extract,
+    whitespace: bool,
+    no_split: bool,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -47,6 +47,14 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Field delimiter")
                 .default_value("\t"),
         )
+        .arg(
+            Arg::with_name("whitespace")
+                .short("w")
+                .long("whitespace")
+                .help("Split fields on runs of whitespace")
+                .requires("fields")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("bytes")
                 .value_name("BYTES")
@@ -55,6 +63,13 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Selected bytes")
                 .conflicts_with_all(&["chars", "fields"]),
         )
+        .arg(
+            Arg::with_name("no_split")
+                .short("n")
+                .help("With -b, don't split multibyte characters")
+                .requires("bytes")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("chars")
                 .value_name("CHARS")
@@ -103,6 +118,8 @@ pub fn get_args() -> MyResult<Config> {
         files: matches.values_of_lossy("files").unwrap_or_default(),
         delimiter: *delim_bytes.first().unwrap(),
         extract,
+        whitespace: matches.is_present("whitespace"),
+        no_split: matches.is_present("no_split"),
     })
 }
 
@@ -111,6 +128,16 @@ pub fn run(config: Config) -> MyResult<()> {
         match open(filename) {
             Err(err) => eprint!("{}: {}", filename, err),
             Ok(file) => match &config.extract {
+                // 連続する空白やタブを1つの区切りとみなし、-dの区切り文字でつないで出力する
+                Fields(field_pos) if config.whitespace => {
+                    let delimiter = (config.delimiter as char).to_string();
+                    for line in file.lines() {
+                        let line = line?;
+                        let record =
+                            StringRecord::from(line.split_whitespace().collect::<Vec<_>>());
+                        println!("{}", extract_fields(&record, field_pos).join(&delimiter));
+                    }
+                }
                 Fields(ref field_pos) => {
                     let mut reader = ReaderBuilder::new()
                         .has_headers(false)
@@ -128,12 +155,12 @@ pub fn run(config: Config) -> MyResult<()> {
                 }
                 Bytes(byte_pos) => {
                     for line in file.lines() {
-                        println!("{}", extract_bytes(&line?, &byte_pos));
+                        println!("{}", extract_bytes(&line?, byte_pos, config.no_split));
                     }
                 }
                 Chars(char_pos) => {
                     for line in file.lines() {
-                        println!("{}", extract_chars(&line?, &char_pos));
+                        println!("{}", extract_chars(&line?, char_pos));
                     }
                 }
             },
@@ -170,21 +197,13 @@ fn parse_pos(range: &str) -> MyResult<PositionList> {
 fn parse_index(input: &str) -> Result<usize, String> {
     let value_error = || format!("illegal list value: \"{}\"", input);
 
-    input
-        .starts_with('+')
-        .then(|| Err(value_error()))
-        .unwrap_or_else(|| {
-            input
-                .parse::<NonZeroUsize>()
-                .map(|n| usize::from(n) - 1)
-                .map_err(|_| value_error())
-        })
-}
-
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    if input.starts_with('+') {
+        Err(value_error())
+    } else {
+        input
+            .parse::<NonZeroUsize>()
+            .map(|n| usize::from(n) - 1)
+            .map_err(|_| value_error())
     }
 }
 
@@ -198,12 +217,29 @@ fn extract_chars(line: &str, char_pos: &[Range<usize>]) -> String {
         .collect()
 }
 
-fn extract_bytes(line: &str, byte_pos: &[Range<usize>]) -> String {
+/// no_splitがtrueの場合は、範囲の両端が文字の途中にならないように狭め、
+/// 途中で切れる文字は出力しない
+fn extract_bytes(line: &str, byte_pos: &[Range<usize>], no_split: bool) -> String {
     let bytes = line.as_bytes();
+    let narrow = |range: Range<usize>| {
+        if !no_split {
+            return range;
+        }
+        let mut start = range.start.min(bytes.len());
+        let mut end = range.end.min(bytes.len());
+        while !line.is_char_boundary(start) {
+            start += 1;
+        }
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        start..end
+    };
 
     let bytes: Vec<_> = byte_pos
         .iter()
         .cloned()
+        .map(narrow)
         .flat_map(|range| range.filter_map(|i| bytes.get(i)).copied())
         .collect();
 
@@ -220,6 +256,8 @@ fn extract_fields(record: &StringRecord, field_pos: &[Range<usize>]) -> Vec<Stri
 }
 
 #[cfg(test)]
+// 1要素の範囲の配列は意図したもの
+#[allow(clippy::single_range_in_vec_init)]
 mod unit_tests {
     use super::extract_bytes;
     use super::extract_chars;
@@ -351,12 +389,24 @@ mod unit_tests {
 
     #[test]
     fn test_extract_bytes() {
-        assert_eq!(extract_bytes("ábc", &[0..1]), "�".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..2]), "á".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..3]), "áb".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..4]), "ábc".to_string());
-        assert_eq!(extract_bytes("ábc", &[3..4, 2..3]), "cb".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..2, 5..6]), "á".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..1], false), "�".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..2], false), "á".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..3], false), "áb".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..4], false), "ábc".to_string());
+        assert_eq!(extract_bytes("ábc", &[3..4, 2..3], false), "cb".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..2, 5..6], false), "á".to_string());
+    }
+
+    #[test]
+    fn test_extract_bytes_no_split() {
+        // 「á」の途中で切れる場合は「�」ではなく文字ごと除く
+        assert_eq!(extract_bytes("ábc", &[0..1], true), "".to_string());
+        assert_eq!(extract_bytes("ábc", &[1..2], true), "".to_string());
+        assert_eq!(extract_bytes("ábc", &[1..4], true), "bc".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..3], true), "áb".to_string());
+        assert_eq!(extract_bytes("ábc", &[1..2, 3..4], true), "c".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..10], true), "ábc".to_string());
+        assert_eq!(extract_bytes("ábc", &[5..6], true), "".to_string());
     }
 
     #[test]
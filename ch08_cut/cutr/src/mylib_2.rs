@@ -1,378 +0,0 @@
-use crate::Extract::*;
-use clap::{App, Arg};
-use csv::{ReaderBuilder, StringRecord};
-use regex::Regex;
-use std::{
-    error::Error,
-    fs::File,
-    io::{self, BufRead, BufReader},
-    num::NonZeroUsize,
-    ops::Range,
-};
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
-type PositionList = Vec<Range<usize>>;
-
-#[derive(Debug)]
-pub enum Extract {
-    Fields(PositionList),
-    Bytes(PositionList),
-    Chars(PositionList),
-}
-
-#[derive(Debug)]
-pub struct Config {
-    files: Vec<String>,
-    delimiter: u8,
-    extract: Extract,
-}
-
-pub fn get_args() -> MyResult<Config> {
-    let matches = App::new("cutr")
-        .version("0.1.0")
-        .author("Arinobu Fukuhara <afukuhara@gmail.com>")
-        .about("Rust cut")
-        .arg(
-            Arg::with_name("files")
-                .value_name("FILE")
-                .help("Input file(s)")
-                .multiple(true)
-                .default_value("-"),
-        )
-        .arg(
-            Arg::with_name("delimiter")
-                .value_name("DELEMITER")
-                .short("d")
-                .long("delim")
-                .help("Field delimiter")
-                .default_value("\t"),
-        )
-        .arg(
-            Arg::with_name("bytes")
-                .value_name("BYTES")
-                .short("b")
-                .long("bytes")
-                .help("Selected bytes")
-                .conflicts_with_all(&["chars", "fields"]),
-        )
-        .arg(
-            Arg::with_name("chars")
-                .value_name("CHARS")
-                .short("c")
-                .long("chars")
-                .help("Selected characters")
-                .conflicts_with_all(&["bytes", "fields"]),
-        )
-        .arg(
-            Arg::with_name("fields")
-                .value_name("FIELDS")
-                .short("f")
-                .long("fields")
-                .help("Selected fields")
-                .conflicts_with_all(&["bytes", "chars"]),
-        )
-        .get_matches();
-
-    let delimiter = matches.value_of("delimiter").unwrap();
-    let delim_bytes = delimiter.as_bytes();
-    if delim_bytes.len() != 1 {
-        return Err(From::from(format!(
-            "--delim \"{}\" must be a single byte",
-            delimiter
-        )));
-    }
-
-    let fields = matches.value_of("fields").map(parse_pos).transpose()?;
-    let bytes = matches.value_of("bytes").map(parse_pos).transpose()?;
-    let chars = matches.value_of("chars").map(parse_pos).transpose()?;
-
-    let extract = if let Some(field_pos) = fields {
-        Fields(field_pos)
-    } else if let Some(byte_pos) = bytes {
-        Bytes(byte_pos)
-    } else if let Some(char_pos) = chars {
-        Chars(char_pos)
-    } else {
-        return Err(From::from(
-            "the following required arguments were not provided:\n  \
-        <--fields <FIELDS>|--bytes <BYTES>|--chars <CHARS>>",
-        ));
-    };
-
-    Ok(Config {
-        files: matches.values_of_lossy("files").unwrap_or_default(),
-        delimiter: *delim_bytes.first().unwrap(),
-        extract,
-    })
-}
-
-pub fn run(config: Config) -> MyResult<()> {
-    for filename in &config.files {
-        match open(filename) {
-            Err(err) => eprint!("{}: {}", filename, err),
-            Ok(mut reader) => match config.extract {
-                Bytes(ref byte_pos) => loop {
-                    let mut line = String::new();
-                    let line_bytes = reader.read_line(&mut line)?;
-                    if line_bytes == 0 {
-                        break;
-                    }
-
-                    let result: String = extract_bytes(&line, byte_pos);
-                    println!("{}", result);
-                },
-                Chars(ref char_pos) => loop {
-                    let mut line = String::new();
-                    let line_bytes = reader.read_line(&mut line)?;
-                    if line_bytes == 0 {
-                        break;
-                    }
-
-                    let result = extract_chars(&line, char_pos);
-                    println!("{}", result);
-                },
-                Fields(ref field_pos) => {
-                    let mut reader = ReaderBuilder::new()
-                        .has_headers(false)
-                        .delimiter(config.delimiter)
-                        .from_reader(reader);
-                    for record in reader.records() {
-                        let results = extract_fields(&record?, field_pos);
-                        println!("{}", results.join(&(config.delimiter as char).to_string()));
-                    }
-                }
-            },
-        }
-    }
-    Ok(())
-}
-
-fn parse_pos(range: &str) -> MyResult<PositionList> {
-    let range_re = Regex::new(r"^(\d+)-(\d+)$").unwrap();
-
-    range
-        .split(',')
-        .map(|val| {
-            parse_index(val).map(|n| n..n + 1).or_else(|e| {
-                range_re.captures(val).ok_or(e).and_then(|captures| {
-                    let n1 = parse_index(&captures[1])?;
-                    let n2 = parse_index(&captures[2])?;
-                    if n1 >= n2 {
-                        return Err(format!(
-                            "First number in range ({}) must be lower than second number ({})",
-                            n1 + 1,
-                            n2 + 1
-                        ));
-                    }
-                    Ok(n1..n2 + 1)
-                })
-            })
-        })
-        .collect::<Result<_, _>>()
-        .map_err(From::from)
-}
-
-fn parse_index(input: &str) -> Result<usize, String> {
-    let value_error = || format!("illegal list value: \"{}\"", input);
-
-    input
-        .starts_with('+')
-        .then(|| Err(value_error()))
-        .unwrap_or_else(|| {
-            input
-                .parse::<NonZeroUsize>()
-                .map(|n| usize::from(n) - 1)
-                .map_err(|_| value_error())
-        })
-}
-
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
-    }
-}
-
-fn extract_chars(line: &str, char_pos: &[Range<usize>]) -> String {
-    char_pos
-        .iter()
-        .flat_map(|range| {
-            line.chars() // char_indices() の代わりに chars() を使用
-                .skip(range.start)
-                .take(range.end.saturating_sub(range.start))
-        })
-        .collect()
-}
-
-fn extract_bytes(line: &str, byte_pos: &[Range<usize>]) -> String {
-    let bytes: Vec<u8> = byte_pos
-        .iter()
-        .flat_map(|range| line.as_bytes().get(range.clone()).unwrap_or(&[]).to_vec())
-        .collect();
-
-    String::from_utf8_lossy(&bytes).into_owned()
-}
-
-fn extract_fields(record: &StringRecord, field_pos: &[Range<usize>]) -> Vec<String> {
-    field_pos
-        .iter()
-        .flat_map(|range| {
-            record
-                .iter()
-                .enumerate()
-                .filter(|(i, _v)| range.contains(i))
-                .map(|(_, f)| f.to_string())
-        })
-        .collect::<Vec<String>>()
-}
-
-#[cfg(test)]
-mod unit_tests {
-    use super::extract_bytes;
-    use super::extract_chars;
-    use super::extract_fields;
-    use super::parse_pos;
-    use csv::StringRecord;
-
-    #[test]
-    fn test_parse_pos() {
-        // 空文字列はエラー
-        assert!(parse_pos("").is_err());
-
-        // ゼロはエラー
-        let res = parse_pos("0");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"0\"",);
-
-        let res = parse_pos("0-1");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"0\"",);
-
-        // 数字の前に「+」が付く場合はエラー
-        let res = parse_pos("+1");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"+1\"",);
-
-        let res = parse_pos("+1-2");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"+1-2\"",);
-
-        let res = parse_pos("1-+2");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"1-+2\"",);
-
-        // 数字以外はエラー
-        let res = parse_pos("a");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"a\"",);
-
-        let res = parse_pos("1,a");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"a\"",);
-
-        let res = parse_pos("1-a");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"1-a\"",);
-
-        let res = parse_pos("a-1");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"a-1\"",);
-
-        // エラーになる範囲
-        let res = parse_pos("-");
-        assert!(res.is_err());
-
-        let res = parse_pos(",");
-        assert!(res.is_err());
-
-        let res = parse_pos("1,");
-        assert!(res.is_err());
-
-        let res = parse_pos("1-");
-        assert!(res.is_err());
-
-        let res = parse_pos("1-1-1");
-        assert!(res.is_err());
-
-        let res = parse_pos("1-1-a");
-        assert!(res.is_err());
-
-        // 最初の数字は2番目より小さい必要がある
-        let res = parse_pos("1-1");
-        assert!(res.is_err());
-        assert_eq!(
-            res.unwrap_err().to_string(),
-            "First number in range (1) must be lower than second number (1)"
-        );
-
-        let res = parse_pos("2-1");
-        assert!(res.is_err());
-        assert_eq!(
-            res.unwrap_err().to_string(),
-            "First number in range (2) must be lower than second number (1)"
-        );
-
-        // 以下のケースは受け入れられる
-        let res = parse_pos("1");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1]);
-
-        let res = parse_pos("01");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1]);
-
-        let res = parse_pos("1,3");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1, 2..3]);
-
-        let res = parse_pos("001,0003");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1, 2..3]);
-
-        let res = parse_pos("1-3");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..3]);
-
-        let res = parse_pos("0001-03");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..3]);
-
-        let res = parse_pos("1,7,3-5");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1, 6..7, 2..5]);
-
-        let res = parse_pos("15,19-20");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![14..15, 18..20]);
-    }
-
-    #[test]
-    fn test_extract_chars() {
-        assert_eq!(extract_chars("", &[0..1]), "".to_string());
-        assert_eq!(extract_chars("ábc", &[0..1]), "á".to_string());
-        assert_eq!(extract_chars("ábc", &[0..1, 2..3]), "ác".to_string());
-        assert_eq!(extract_chars("ábc", &[0..3]), "ábc".to_string());
-        assert_eq!(extract_chars("ábc", &[2..3, 1..2]), "cb".to_string());
-        assert_eq!(extract_chars("ábc", &[0..1, 1..2, 4..5]), "áb".to_string());
-    }
-
-    #[test]
-    fn test_extract_bytes() {
-        assert_eq!(extract_bytes("ábc", &[0..1]), "�".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..2]), "á".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..3]), "áb".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..4]), "ábc".to_string());
-        assert_eq!(extract_bytes("ábc", &[3..4, 2..3]), "cb".to_string());
-        assert_eq!(extract_bytes("ábc", &[0..2, 5..6]), "á".to_string());
-    }
-
-    #[test]
-    fn test_extract_fields() {
-        let rec = StringRecord::from(vec!["Captain", "Sham", "12345"]);
-        assert_eq!(extract_fields(&rec, &[0..1]), &["Captain"]);
-        assert_eq!(extract_fields(&rec, &[1..2]), &["Sham"]);
-        assert_eq!(extract_fields(&rec, &[0..1, 2..3]), &["Captain", "12345"]);
-        assert_eq!(extract_fields(&rec, &[0..1, 3..4]), &["Captain"]);
-        assert_eq!(extract_fields(&rec, &[1..2, 0..1]), &["Sham", "Captain"]);
-    }
-}
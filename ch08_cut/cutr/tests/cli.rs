@@ -110,6 +110,22 @@ fn dies_bad_delimiter() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_multibyte_delimiter() -> Result<()> {
+    // 1文字でも複数バイトの区切り文字は切り詰めずにエラーにする
+    dies(
+        &[CSV, "-f", "1", "-d", "é"],
+        r#"--delim "é" must be a single byte"#,
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_whitespace_without_fields() -> Result<()> {
+    dies(&[CSV, "-w", "-c", "1"], "--fields <FIELDS>")
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_chars_bytes_fields() -> Result<()> {
@@ -340,3 +356,58 @@ fn tsv_c1_8() -> Result<()> {
 fn repeated_value() -> Result<()> {
     run(&[BOOKS, "-c", "1,1"], "tests/expected/books.c1,1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn whitespace_fields() -> Result<()> {
+    // 連続する空白やタブは1つの区切りとして扱い、行頭の空白は無視する
+    let input = "a  b\t\tc\n  d e\n";
+    Command::cargo_bin(PRG)?
+        .args(["-w", "-f", "2"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("b\ne\n");
+
+    // 出力は-dの区切り文字でつなぐ
+    Command::cargo_bin(PRG)?
+        .args(["--whitespace", "-f", "1,3", "-d", ","])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("a,c\nd\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_no_split() -> Result<()> {
+    // -nの場合は途中で切れるマルチバイト文字を出力しない
+    Command::cargo_bin(PRG)?
+        .args(["-n", "-b", "1,3-4"])
+        .write_stdin("ábc\n")
+        .assert()
+        .success()
+        .stdout("bc\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fields_requested_order() -> Result<()> {
+    // -cや-bと同じく、フィールドも指定した順番で出力する
+    Command::cargo_bin(PRG)?
+        .args(["-f", "2,1"])
+        .write_stdin("a\tb\tc\n")
+        .assert()
+        .success()
+        .stdout("b\ta\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-f", "3,1-2", "-d", ","])
+        .write_stdin("a,b,c\n")
+        .assert()
+        .success()
+        .stdout("c,a,b\n");
+    Ok(())
+}
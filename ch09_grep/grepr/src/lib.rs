@@ -1,25 +1,97 @@
 use clap::{App, Arg};
+use common::MyResult;
+use flate2::bufread::MultiGzDecoder;
+use glob::Pattern;
 use regex::{Regex, RegexBuilder};
-use std::error::Error;
-use std::fs::{self, File};
-use std::io::{self, BufRead, BufReader};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write};
 use std::mem;
+use std::time::Instant;
 use std::vec;
 use walkdir::WalkDir;
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
+// GNU grepと同じ色でマッチした箇所を強調する
+const MATCH_START: &str = "\x1b[01;31m\x1b[K";
+const MATCH_END: &str = "\x1b[m\x1b[K";
 
 #[derive(Debug)]
 pub struct Config {
     pattern: Regex,
     files: Vec<String>,
     recursive: bool,
+    follow_links: bool,
     count: bool,
     invert_match: bool,
+    line_number: bool,
+    byte_offset: bool,
+    before_context: usize,
+    after_context: usize,
+    only_matching: bool,
+    json: bool,
+    files_with_matches: bool,
+    files_without_match: bool,
+    max_count: Option<usize>,
+    includes: Vec<Pattern>,
+    excludes: Vec<Pattern>,
+    with_filename: Option<bool>,
+    stats: bool,
+    passthru: bool,
+    color: bool,
+    binary_files: BinaryFiles,
+}
+
+/// --binary-filesで指定するバイナリファイルの扱い
+#[derive(Debug, PartialEq)]
+enum BinaryFiles {
+    // マッチした行の代わりに「Binary file ... matches」と表示する
+    Binary,
+    // テキストファイルと同じように扱う
+    Text,
+    // マッチしないファイルとして扱う
+    WithoutMatch,
+}
+
+// --statsで表示する集計値
+#[derive(Debug, Default)]
+struct Stats {
+    files: usize,
+    matched_files: usize,
+    matches: usize,
+}
+
+/// 検索の結果。mainで終了コードを決めるのに使う
+#[derive(Debug, Default, PartialEq)]
+pub struct GrepOutcome {
+    pub matched: bool,
+    pub error: bool,
+}
+
+impl GrepOutcome {
+    /// GNU grepと同じく、エラーがあれば2、マッチがあれば0、なければ1
+    pub fn exit_code(&self) -> i32 {
+        if self.error {
+            2
+        } else if self.matched {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Line {
+    num: usize,
+    // ファイルの先頭からの行の開始位置（バイト）
+    offset: usize,
+    text: String,
+    is_match: bool,
 }
 
 pub fn get_args() -> MyResult<Config> {
-    let matches = App::new("cutr")
+    let matches = App::new("grepr")
         .version("0.1.0")
         .author("Arinobu Fukuhara <afukuhara@gmail.com>")
         .about("Rust grep")
@@ -27,7 +99,16 @@ pub fn get_args() -> MyResult<Config> {
             Arg::with_name("pattern")
                 .value_name("PATTERN")
                 .help("Search pattern")
-                .required(true),
+                .required_unless("pattern_file"),
+        )
+        .arg(
+            Arg::with_name("pattern_file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("Read patterns from FILE, one per line")
+                .multiple(true)
+                .number_of_values(1),
         )
         .arg(
             Arg::with_name("files")
@@ -64,48 +145,497 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Recursive search")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("dereference_recursive")
+                .short("R")
+                .long("dereference-recursive")
+                .help("Recursive search, following symbolic links")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("line_number")
+                .short("n")
+                .long("line-number")
+                .help("Prefix each line with its line number")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("byte_offset")
+                .short("b")
+                .long("byte-offset")
+                .help("Prefix each line with its 0-based byte offset")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("after_context")
+                .short("A")
+                .long("after-context")
+                .value_name("NUM")
+                .help("Print NUM lines of trailing context"),
+        )
+        .arg(
+            Arg::with_name("before_context")
+                .short("B")
+                .long("before-context")
+                .value_name("NUM")
+                .help("Print NUM lines of leading context"),
+        )
+        .arg(
+            Arg::with_name("context")
+                .short("C")
+                .long("context")
+                .value_name("NUM")
+                .help("Print NUM lines of output context"),
+        )
+        .arg(
+            Arg::with_name("only_matching")
+                .short("o")
+                .long("only-matching")
+                .help("Print only the matched parts of a line")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Print one JSON object per match")
+                .takes_value(false)
+                .conflicts_with("count"),
+        )
+        .arg(
+            Arg::with_name("files_with_matches")
+                .short("l")
+                .long("files-with-matches")
+                .help("Print only names of files with a match")
+                .takes_value(false)
+                .conflicts_with("files_without_match"),
+        )
+        .arg(
+            Arg::with_name("files_without_match")
+                .short("L")
+                .long("files-without-match")
+                .help("Print only names of files without a match")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("word_regexp")
+                .short("w")
+                .long("word-regexp")
+                .help("Match only whole words")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("line_regexp")
+                .short("x")
+                .long("line-regexp")
+                .help("Match only whole lines")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("max_count")
+                .short("m")
+                .long("max-count")
+                .value_name("NUM")
+                .help("Stop reading a file after NUM matching lines"),
+        )
+        .arg(
+            Arg::with_name("with_filename")
+                .short("H")
+                .long("with-filename")
+                .help("Always print the filename with output lines")
+                .overrides_with("no_filename")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("no_filename")
+                .short("h")
+                .long("no-filename")
+                .help("Never print the filename with output lines")
+                .overrides_with("with_filename")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("passthru")
+                .long("passthru")
+                .help("Print all lines, highlighting matches")
+                .conflicts_with_all(&["count", "only_matching", "json"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("Highlight matches")
+                .possible_values(&["never", "always", "auto"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::with_name("binary_files")
+                .long("binary-files")
+                .value_name("TYPE")
+                .help("Assume that binary files are TYPE")
+                .possible_values(&["binary", "text", "without-match"])
+                .default_value("binary"),
+        )
+        .arg(
+            Arg::with_name("without_match")
+                .short("I")
+                .help("Equivalent to --binary-files=without-match")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .help("Print a summary of the search to STDERR")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("include")
+                .long("include")
+                .value_name("GLOB")
+                .help("Search only files whose name matches GLOB")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("exclude")
+                .long("exclude")
+                .value_name("GLOB")
+                .help("Skip files whose name matches GLOB")
+                .multiple(true)
+                .number_of_values(1),
+        )
         .get_matches();
 
-    let pattern = matches.value_of("pattern").unwrap();
-    let regex = RegexBuilder::new(pattern)
-        .case_insensitive(matches.is_present("insensitive"))
-        .build()
-        .map_err(|_e| format!("Invalid pattern \"{}\"", pattern))?;
+    // -fの場合はPATTERNを取らないので、最初の位置引数もファイルとして扱う
+    let (patterns, files) = match matches.values_of_lossy("pattern_file") {
+        Some(pattern_files) => {
+            let mut files: Vec<String> = matches
+                .value_of("pattern")
+                .map(String::from)
+                .into_iter()
+                .collect();
+            if files.is_empty() || matches.occurrences_of("files") > 0 {
+                files.extend(matches.values_of_lossy("files").unwrap());
+            }
+            (read_patterns(&pattern_files)?, files)
+        }
+        None => (
+            vec![matches.value_of("pattern").unwrap().to_string()],
+            matches.values_of_lossy("files").unwrap(),
+        ),
+    };
+
+    // 複数のパターンは選択にまとめ、どれかにマッチすればよいことにする
+    let regex = build_regex(
+        &join_patterns(&patterns),
+        matches.is_present("insensitive"),
+        matches.is_present("word_regexp"),
+        matches.is_present("line_regexp"),
+    )
+    .map_err(|_e| {
+        let invalid = patterns
+            .iter()
+            .find(|pattern| Regex::new(pattern).is_err())
+            .unwrap_or(&patterns[0]);
+        format!("Invalid pattern \"{}\"", invalid)
+    })?;
+
+    let max_count = matches
+        .value_of("max_count")
+        .map(|v| {
+            v.parse::<usize>()
+                .map_err(|_| format!("invalid max count \"{}\"", v))
+        })
+        .transpose()?;
+    let includes = parse_globs(matches.values_of_lossy("include"))?;
+    let excludes = parse_globs(matches.values_of_lossy("exclude"))?;
+    let context = parse_context(matches.value_of("context"))?;
+    let after_context = parse_context(matches.value_of("after_context"))?.or(context);
+    let before_context = parse_context(matches.value_of("before_context"))?.or(context);
 
     Ok(Config {
         pattern: regex,
-        files: matches.values_of_lossy("files").unwrap(),
-        recursive: matches.is_present("recursive"),
+        files,
+        recursive: matches.is_present("recursive") || matches.is_present("dereference_recursive"),
+        follow_links: matches.is_present("dereference_recursive"),
         count: matches.is_present("count"),
         invert_match: matches.is_present("invert-match"),
+        line_number: matches.is_present("line_number"),
+        byte_offset: matches.is_present("byte_offset"),
+        before_context: before_context.unwrap_or(0),
+        after_context: after_context.unwrap_or(0),
+        only_matching: matches.is_present("only_matching"),
+        json: matches.is_present("json"),
+        files_with_matches: matches.is_present("files_with_matches"),
+        files_without_match: matches.is_present("files_without_match"),
+        max_count,
+        includes,
+        excludes,
+        with_filename: if matches.is_present("with_filename") {
+            Some(true)
+        } else if matches.is_present("no_filename") {
+            Some(false)
+        } else {
+            None
+        },
+        stats: matches.is_present("stats"),
+        passthru: matches.is_present("passthru"),
+        color: match matches.value_of("color") {
+            Some("always") => true,
+            Some("auto") => io::stdout().is_terminal(),
+            _ => false,
+        },
+        binary_files: match matches.value_of("binary_files") {
+            _ if matches.is_present("without_match") => BinaryFiles::WithoutMatch,
+            Some("text") => BinaryFiles::Text,
+            Some("without-match") => BinaryFiles::WithoutMatch,
+            _ => BinaryFiles::Binary,
+        },
+    })
+}
+
+/// -fで指定したファイルから1行に1つずつパターンを読む。空行は飛ばす
+fn read_patterns(pattern_files: &[String]) -> MyResult<Vec<String>> {
+    let mut patterns = vec![];
+    for filename in pattern_files {
+        let contents = fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        patterns.extend(
+            contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(String::from),
+        );
+    }
+    Ok(patterns)
+}
+
+/// パターンを1つの正規表現の選択にまとめる。
+/// パターンがない場合は、GNU grepと同じくどの行にもマッチしないようにする
+fn join_patterns(patterns: &[String]) -> String {
+    if patterns.is_empty() {
+        return r"[^\s\S]".to_string();
+    }
+    patterns
+        .iter()
+        .map(|pattern| format!("(?:{})", pattern))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn build_regex(
+    pattern: &str,
+    insensitive: bool,
+    word_regexp: bool,
+    line_regexp: bool,
+) -> MyResult<Regex> {
+    // -wの場合は単語の境界でのみマッチするようにパターンを囲む。
+    // regexクレートの\bはUnicodeの単語の文字を扱うので、アクセント付きの文字も単語の一部になる
+    let pattern = if line_regexp {
+        // 読んだ行は改行を含むので、行末は改行の直前か末尾にする。
+        // GNU grepと同じく-wより優先する
+        format!(r"\A(?:{})(?m:$)", pattern)
+    } else if word_regexp {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    Ok(RegexBuilder::new(&pattern)
+        .case_insensitive(insensitive)
+        .build()?)
+}
+
+fn parse_globs(vals: Option<Vec<String>>) -> MyResult<Vec<Pattern>> {
+    vals.unwrap_or_default()
+        .iter()
+        .map(|val| Pattern::new(val).map_err(|_| From::from(format!("Invalid glob \"{}\"", val))))
+        .collect()
+}
+
+fn parse_context(val: Option<&str>) -> MyResult<Option<usize>> {
+    val.map(|v| {
+        v.parse()
+            .map_err(|_| From::from(format!("{}: invalid context length argument", v)))
     })
+    .transpose()
 }
 
-pub fn run(config: Config) -> MyResult<()> {
-    let entries = find_files(&config.files, config.recursive);
-    let num_files = entries.len();
+pub fn run(config: Config) -> MyResult<GrepOutcome> {
+    let entries = find_files(
+        &config.files,
+        config.recursive,
+        config.follow_links,
+        &config.includes,
+        &config.excludes,
+    );
+    // -H/-hの指定がなければ複数ファイルの場合にだけファイル名を表示する
+    let with_filename = config.with_filename.unwrap_or(entries.len() > 1);
+    let has_context = config.before_context > 0 || config.after_context > 0;
+    let mut printed_any = false;
+    let started = Instant::now();
+    let mut stats = Stats::default();
+    let mut outcome = GrepOutcome::default();
+    // 多くのファイルを探す場合に備えて、標準出力は1回だけロックしてバッファする。
+    // エラーを表示する前にはflushして、標準エラー出力との順番を保つ
+    let mut out = BufWriter::new(io::stdout().lock());
 
-    let print = |fname: &str, val: &str| {
-        if num_files > 1 {
-            print!("{}:{}", fname, val);
-        } else {
-            print!("{}", val);
+    // マッチした行は「:」、前後の文脈行は「-」で区切る
+    let print = |out: &mut dyn Write,
+                 fname: &str,
+                 sep: char,
+                 line_num: Option<usize>,
+                 offset: Option<usize>,
+                 val: &str|
+     -> io::Result<()> {
+        if with_filename {
+            write!(out, "{}{}", fname, sep)?;
+        }
+        if let Some(num) = line_num {
+            write!(out, "{}{}", num, sep)?;
         }
+        if let Some(offset) = offset {
+            write!(out, "{}{}", offset, sep)?;
+        }
+        write!(out, "{}", val)
     };
 
     for entry in entries {
         match entry {
-            Err(e) => eprintln!("{}", e),
-            Ok(filename) => match open(&filename) {
-                Err(e) => eprintln!("{}: {}", filename, e),
-                Ok(file) => match find_lines(file, &config.pattern, config.invert_match) {
-                    Err(e) => eprintln!("{}", e),
-                    Ok(matches) => {
+            Err(e) => {
+                out.flush()?;
+                eprintln!("{}", e);
+                outcome.error = true;
+            }
+            Ok(filename) => match open_with_binary(&filename) {
+                Err(e) => {
+                    out.flush()?;
+                    eprintln!("{}: {}", filename, e);
+                    outcome.error = true;
+                }
+                Ok((_, true)) if config.binary_files == BinaryFiles::WithoutMatch => {
+                    // -Iの場合は読まずにマッチしないファイルとして扱う
+                    stats.files += 1;
+                    if config.files_without_match {
+                        writeln!(out, "{}", filename)?;
+                        outcome.matched = true;
+                    }
+                }
+                Ok((file, _)) if config.files_with_matches || config.files_without_match => {
+                    stats.files += 1;
+                    match file_matches(file, &config.pattern, config.invert_match) {
+                        Err(e) => {
+                            out.flush()?;
+                            eprintln!("{}", e);
+                            outcome.error = true;
+                        }
+                        Ok(is_match) => {
+                            stats.matched_files += is_match as usize;
+                            // -Lの場合はファイル名を表示したときに成功とする
+                            if is_match == config.files_with_matches {
+                                writeln!(out, "{}", filename)?;
+                                outcome.matched = true;
+                            }
+                        }
+                    }
+                }
+                Ok((file, is_binary)) => match select_lines(file, &config) {
+                    Err(e) => {
+                        out.flush()?;
+                        eprintln!("{}", e);
+                        outcome.error = true;
+                    }
+                    Ok(lines) => {
+                        let matched = lines.iter().filter(|line| line.is_match);
+                        let num_matched = matched.clone().count();
+                        outcome.matched |= num_matched > 0;
+                        stats.files += 1;
+                        stats.matched_files += (num_matched > 0) as usize;
+                        stats.matches += num_matched;
                         if config.count {
-                            print(&filename, &format!("{}\n", matches.len()));
+                            // -oの場合は行数ではなくマッチした箇所の数を数える
+                            let count: usize = if config.only_matching {
+                                matched
+                                    .map(|line| find_parts(&config.pattern, line).len())
+                                    .sum()
+                            } else {
+                                num_matched
+                            };
+                            print(
+                                &mut out,
+                                &filename,
+                                ':',
+                                None,
+                                None,
+                                &format!("{}\n", count),
+                            )?;
+                        } else if config.json {
+                            for line in matched {
+                                for record in json_records(&filename, &config.pattern, line) {
+                                    writeln!(out, "{}", record)?;
+                                }
+                            }
+                        } else if is_binary && config.binary_files == BinaryFiles::Binary {
+                            // バイナリファイルの行は表示せず、マッチしたことだけを伝える
+                            if num_matched > 0 {
+                                writeln!(
+                                    out,
+                                    "Binary file {} matches",
+                                    if filename == "-" {
+                                        "(standard input)"
+                                    } else {
+                                        &filename
+                                    }
+                                )?;
+                            }
+                        } else if config.only_matching {
+                            for line in matched {
+                                let line_num = config.line_number.then_some(line.num);
+                                // -bの場合は行ではなくマッチした箇所の位置を表示する
+                                for (offset, part) in find_parts(&config.pattern, line) {
+                                    let part = if config.color {
+                                        format!("{}{}{}", MATCH_START, part, MATCH_END)
+                                    } else {
+                                        part.to_string()
+                                    };
+                                    print(
+                                        &mut out,
+                                        &filename,
+                                        ':',
+                                        line_num,
+                                        config.byte_offset.then_some(offset),
+                                        &format!("{}\n", part),
+                                    )?;
+                                }
+                            }
                         } else {
-                            for line in &matches {
-                                print(&filename, line);
+                            let mut prev_num: Option<usize> = None;
+                            for line in &lines {
+                                // 連続しないグループの間には「--」を表示する
+                                let is_new_group = match prev_num {
+                                    Some(prev) => line.num != prev + 1,
+                                    None => printed_any,
+                                };
+                                if has_context && is_new_group {
+                                    writeln!(out, "--")?;
+                                }
+
+                                let sep = if line.is_match { ':' } else { '-' };
+                                let line_num = config.line_number.then_some(line.num);
+                                let offset = config.byte_offset.then_some(line.offset);
+                                if config.color && line.is_match && !config.invert_match {
+                                    print(
+                                        &mut out,
+                                        &filename,
+                                        sep,
+                                        line_num,
+                                        offset,
+                                        &highlight(&config.pattern, &line.text),
+                                    )?;
+                                } else {
+                                    print(&mut out, &filename, sep, line_num, offset, &line.text)?;
+                                }
+                                prev_num = Some(line.num);
+                                printed_any = true;
                             }
                         }
                     }
@@ -113,12 +643,105 @@ pub fn run(config: Config) -> MyResult<()> {
             },
         }
     }
-    Ok(())
+
+    out.flush()?;
+    if config.stats {
+        eprintln!(
+            "files searched: {}, files with matches: {}, matches: {}, elapsed: {:.3}s",
+            stats.files,
+            stats.matched_files,
+            stats.matches,
+            started.elapsed().as_secs_f64()
+        );
+    }
+    Ok(outcome)
+}
+
+/// パターンにマッチする行があるかどうかを返す。最初にマッチした時点で読むのをやめる
+fn file_matches<T: BufRead>(mut file: T, pattern: &Regex, invert_match: bool) -> MyResult<bool> {
+    let mut line = String::new();
+
+    loop {
+        let bytes = read_line_lossy(&mut file, &mut line)?;
+        if bytes == 0 {
+            return Ok(false);
+        }
+
+        if pattern.is_match(&line) ^ invert_match {
+            return Ok(true);
+        }
+
+        line.clear();
+    }
 }
 
-fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
+/// マッチした箇所をエスケープシーケンスで囲む。行末の改行はそのまま残す
+fn highlight(pattern: &Regex, text: &str) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for m in pattern
+        .find_iter(text.trim_end_matches('\n'))
+        .filter(|m| !m.is_empty())
+    {
+        highlighted.push_str(&text[last..m.start()]);
+        highlighted.push_str(&format!("{}{}{}", MATCH_START, m.as_str(), MATCH_END));
+        last = m.end();
+    }
+    highlighted.push_str(&text[last..]);
+    highlighted
+}
+
+/// 行の中でパターンにマッチした部分（空でないもの）を、ファイルの先頭からの
+/// 位置（バイト）とともにすべて返す
+fn find_parts<'a>(pattern: &Regex, line: &'a Line) -> Vec<(usize, &'a str)> {
+    pattern
+        .find_iter(line.text.trim_end_matches('\n'))
+        .filter(|m| !m.is_empty())
+        .map(|m| (line.offset + m.start(), m.as_str()))
+        .collect()
+}
+
+/// マッチした箇所ごとに、ファイル名・行番号・行・マッチ範囲（バイト位置）を
+/// 持つJSONオブジェクトを作る。-vで選ばれた行のように範囲がない場合はnullにする
+fn json_records(filename: &str, pattern: &Regex, line: &Line) -> Vec<Value> {
+    let text = line.text.trim_end_matches('\n');
+    let record = |start: Option<usize>, end: Option<usize>| {
+        json!({
+            "file": filename,
+            "line_number": line.num,
+            "line": text,
+            "match_start": start,
+            "match_end": end,
+        })
+    };
+
+    let records: Vec<Value> = pattern
+        .find_iter(text)
+        .map(|m| record(Some(m.start()), Some(m.end())))
+        .collect();
+    if records.is_empty() {
+        vec![record(None, None)]
+    } else {
+        records
+    }
+}
+
+/// follow_linksがtrue（-R）の場合は、再帰的に検索するときにシンボリックリンクもたどる
+fn find_files(
+    paths: &[String],
+    recursive: bool,
+    follow_links: bool,
+    includes: &[Pattern],
+    excludes: &[Pattern],
+) -> Vec<MyResult<String>> {
     let mut results = vec![];
 
+    // 再帰的に検索する場合はファイル名で絞り込む
+    let is_selected = |name: &str| {
+        (includes.is_empty() || includes.iter().any(|glob| glob.matches(name)))
+            && !excludes.iter().any(|glob| glob.matches(name))
+    };
+
     for path in paths {
         match path.as_str() {
             "-" => results.push(Ok(path.to_string())),
@@ -126,12 +749,18 @@ fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
                 Ok(metadata) => {
                     if metadata.is_dir() {
                         if recursive {
-                            for entry in WalkDir::new(path)
-                                .into_iter()
-                                .flatten()
-                                .filter(|e| e.file_type().is_file())
-                            {
-                                results.push(Ok(entry.path().display().to_string()));
+                            // リンクのループなどで読めなかったエントリはエラーとして返す
+                            for entry in WalkDir::new(path).follow_links(follow_links) {
+                                match entry {
+                                    Err(e) => results.push(Err(From::from(e.to_string()))),
+                                    Ok(entry) => {
+                                        if entry.file_type().is_file()
+                                            && is_selected(&entry.file_name().to_string_lossy())
+                                        {
+                                            results.push(Ok(entry.path().display().to_string()));
+                                        }
+                                    }
+                                }
                             }
                         } else {
                             results.push(Err(From::from(format!("{} is a directory", path))));
@@ -149,39 +778,142 @@ fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
 }
 
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    gunzip(common::open(filename)?)
+}
+
+/// 開いたファイルと、バイナリファイルかどうかを返す。
+/// 最初に読み込んだ部分にNULがあればバイナリファイルとみなす
+fn open_with_binary(filename: &str) -> MyResult<(Box<dyn BufRead>, bool)> {
+    let mut file = open(filename)?;
+    let is_binary = file.fill_buf()?.contains(&0);
+    Ok((file, is_binary))
+}
+
+/// 先頭のマジックナンバー（1f 8b）でgzipと判定したら展開しながら読む。
+/// `cat a.gz b.gz > c.gz`のように連結されたファイルもすべて展開する
+fn gunzip<R: BufRead + 'static>(mut reader: R) -> MyResult<Box<dyn BufRead>> {
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
     }
 }
 
-fn find_lines<T: BufRead>(
+/// 1行をバイト列として読み、UTF-8として正しくない部分はU+FFFDに置き換えて`line`に追加する。
+/// バイナリファイルでも読み込みエラーにせずに検索できる。戻り値は読み込んだバイト数
+fn read_line_lossy<T: BufRead>(file: &mut T, line: &mut String) -> io::Result<usize> {
+    let mut buf = vec![];
+    let bytes = file.read_until(b'\n', &mut buf)?;
+    line.push_str(&String::from_utf8_lossy(&buf));
+    Ok(bytes)
+}
+
+// --passthruの場合はすべての行を返す
+fn select_lines<T: BufRead>(file: T, config: &Config) -> MyResult<Vec<Line>> {
+    if config.passthru {
+        passthru_lines(file, &config.pattern, config.invert_match)
+    } else {
+        find_lines(
+            file,
+            &config.pattern,
+            config.invert_match,
+            config.before_context,
+            config.after_context,
+            config.max_count,
+        )
+    }
+}
+
+fn passthru_lines<T: BufRead>(
     mut file: T,
     pattern: &Regex,
     invert_match: bool,
-) -> MyResult<Vec<String>> {
+) -> MyResult<Vec<Line>> {
     let mut line = String::new();
-    let mut matches = vec![];
+    let mut lines = vec![];
+    let mut offset = 0;
 
     loop {
-        let bytes = file.read_line(&mut line)?;
+        let bytes = read_line_lossy(&mut file, &mut line)?;
         if bytes == 0 {
             break;
         }
+        lines.push(Line {
+            num: lines.len() + 1,
+            offset,
+            is_match: pattern.is_match(&line) ^ invert_match,
+            text: mem::take(&mut line),
+        });
+        offset += bytes;
+    }
+    Ok(lines)
+}
 
-        if pattern.is_match(&line) ^ invert_match {
-            matches.push(mem::take(&mut line));
+fn find_lines<T: BufRead>(
+    mut file: T,
+    pattern: &Regex,
+    invert_match: bool,
+    before_context: usize,
+    after_context: usize,
+    max_count: Option<usize>,
+) -> MyResult<Vec<Line>> {
+    let mut line = String::new();
+    let mut lines = vec![];
+    let mut before: VecDeque<Line> = VecDeque::with_capacity(before_context);
+    let mut after_remaining = 0;
+    let mut line_num = 0;
+    let mut offset = 0;
+    let mut num_matches = 0;
+
+    loop {
+        // -mの件数に達したら、後ろの文脈行を出し終えた時点で読むのをやめる
+        let reached_max = max_count.is_some_and(|max| num_matches >= max);
+        if reached_max && after_remaining == 0 {
+            break;
         }
 
-        line.clear();
+        let bytes = read_line_lossy(&mut file, &mut line)?;
+        if bytes == 0 {
+            break;
+        }
+        line_num += 1;
+
+        let is_match = !reached_max && (pattern.is_match(&line) ^ invert_match);
+        let current = Line {
+            num: line_num,
+            offset,
+            text: mem::take(&mut line),
+            is_match,
+        };
+        offset += bytes;
+
+        if is_match {
+            lines.extend(before.drain(..));
+            lines.push(current);
+            after_remaining = after_context;
+            num_matches += 1;
+        } else if after_remaining > 0 {
+            lines.push(current);
+            after_remaining -= 1;
+        } else if before_context > 0 {
+            // 直前のN行だけをリングバッファに保持する
+            if before.len() == before_context {
+                before.pop_front();
+            }
+            before.push_back(current);
+        }
     }
 
-    Ok(matches)
+    Ok(lines)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{find_files, find_lines};
+    use super::{
+        build_regex, file_matches, find_files, find_lines, find_parts, highlight, join_patterns,
+        json_records, Line,
+    };
+    use glob::Pattern;
     use rand::{distributions::Alphanumeric, Rng};
     use regex::{Regex, RegexBuilder};
     use std::io::Cursor;
@@ -189,19 +921,25 @@ mod tests {
     #[test]
     fn test_find_files() {
         // 存在することがわかっているファイルを見つけられることを確認する
-        let files = find_files(&["./tests/inputs/fox.txt".to_string()], false);
+        let files = find_files(
+            &["./tests/inputs/fox.txt".to_string()],
+            false,
+            false,
+            &[],
+            &[],
+        );
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].as_ref().unwrap(), "./tests/inputs/fox.txt");
 
         // recursiveなしの場合、ディレクトリを拒否する
-        let files = find_files(&["./tests/inputs".to_string()], false);
+        let files = find_files(&["./tests/inputs".to_string()], false, false, &[], &[]);
         assert_eq!(files.len(), 1);
         if let Err(e) = &files[0] {
             assert_eq!(e.to_string(), "./tests/inputs is a directory");
         }
 
         // ディレクトリ内の4つのファイルを再帰的に検索できることを確認する
-        let res = find_files(&["./tests/inputs".to_string()], true);
+        let res = find_files(&["./tests/inputs".to_string()], true, false, &[], &[]);
         let mut files: Vec<String> = res
             .iter()
             .map(|r| r.as_ref().unwrap().replace("\\", "/"))
@@ -226,23 +964,75 @@ mod tests {
             .collect();
 
         // エラーとして不正なファイルを返すことを確認する
-        let files = find_files(&[bad], false);
+        let files = find_files(&[bad], false, false, &[], &[]);
         assert_eq!(files.len(), 1);
         assert!(files[0].is_err());
     }
 
+    #[test]
+    fn test_find_files_globs() {
+        let find = |includes: &[&str], excludes: &[&str]| {
+            let globs = |vals: &[&str]| -> Vec<Pattern> {
+                vals.iter().map(|val| Pattern::new(val).unwrap()).collect()
+            };
+            let mut files: Vec<String> = find_files(
+                &["./tests/mixed".to_string()],
+                true,
+                false,
+                &globs(includes),
+                &globs(excludes),
+            )
+            .iter()
+            .map(|r| r.as_ref().unwrap().replace("\\", "/"))
+            .collect();
+            files.sort();
+            files
+        };
+
+        // 指定がなければすべてのファイルを返す
+        assert_eq!(
+            find(&[], &[]),
+            [
+                "./tests/mixed/deps.lock",
+                "./tests/mixed/hello.rs",
+                "./tests/mixed/notes.txt",
+                "./tests/mixed/sub/util.rs",
+            ]
+        );
+
+        // --includeは複数指定でき、いずれかにマッチするファイルを返す
+        assert_eq!(
+            find(&["*.rs"], &[]),
+            ["./tests/mixed/hello.rs", "./tests/mixed/sub/util.rs"]
+        );
+        assert_eq!(
+            find(&["*.rs", "*.txt"], &[]),
+            [
+                "./tests/mixed/hello.rs",
+                "./tests/mixed/notes.txt",
+                "./tests/mixed/sub/util.rs",
+            ]
+        );
+
+        // --excludeは--includeより優先される
+        assert_eq!(
+            find(&["*.rs", "*.lock"], &["*.lock", "util.*"]),
+            ["./tests/mixed/hello.rs"]
+        );
+    }
+
     #[test]
     fn test_find_lines() {
         let text = b"Lorem\nIpsum\r\nDOLOR";
 
         // 「or」というパターンは「Lorem」という1行にマッチするはず
         let re1 = Regex::new("or").unwrap();
-        let matches = find_lines(Cursor::new(&text), &re1, false);
+        let matches = find_lines(Cursor::new(&text), &re1, false, 0, 0, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 1);
 
         // マッチを反転させた場合、残りの2行にマッチするはず
-        let matches = find_lines(Cursor::new(&text), &re1, true);
+        let matches = find_lines(Cursor::new(&text), &re1, true, 0, 0, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 2);
 
@@ -253,13 +1043,234 @@ mod tests {
             .unwrap();
 
         // 「Lorem」と「DOLOR」の2行にマッチするはず
-        let matches = find_lines(Cursor::new(&text), &re2, false);
+        let matches = find_lines(Cursor::new(&text), &re2, false, 0, 0, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 2);
 
         // マッチを反転させた場合、残りの1行にマッチするはず
-        let matches = find_lines(Cursor::new(&text), &re2, true);
+        let matches = find_lines(Cursor::new(&text), &re2, true, 0, 0, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 1);
+
+        // マッチした行の行番号（1始まり）も返すはず
+        let matches = find_lines(Cursor::new(&text), &re2, false, 0, 0, None).unwrap();
+        let line_nums: Vec<usize> = matches.iter().map(|line| line.num).collect();
+        assert_eq!(line_nums, vec![1, 3]);
+
+        // 行の開始位置（バイト）は改行も含めて数える
+        let offsets: Vec<usize> = matches.iter().map(|line| line.offset).collect();
+        assert_eq!(offsets, vec![0, 13]);
+    }
+
+    #[test]
+    fn test_build_regex() {
+        // -wの場合は単語全体にのみマッチする
+        let re = build_regex("cat", false, true, false).unwrap();
+        assert!(re.is_match("the cat"));
+        assert!(re.is_match("cat."));
+        assert!(!re.is_match("category"));
+        assert!(!re.is_match("concat"));
+
+        // -iと組み合わせても大文字と小文字を区別しない
+        let re = build_regex("cat", true, true, false).unwrap();
+        assert!(re.is_match("THE CAT"));
+        assert!(!re.is_match("CATEGORY"));
+
+        // 選択を含むパターンも全体が単語の境界で囲まれる
+        let re = build_regex("cat|dog", false, true, false).unwrap();
+        assert!(re.is_match("hotdog dog"));
+        assert!(!re.is_match("hotdog category"));
+
+        // 不正なパターンはエラーになる
+        assert!(build_regex("*foo", false, true, false).is_err());
+
+        // アクセント付きの文字も単語の文字とみなす
+        let re = build_regex("élan", false, true, false).unwrap();
+        assert!(re.is_match("un élan."));
+        assert!(!re.is_match("élans"));
+        let re = build_regex("caf", false, true, false).unwrap();
+        assert!(!re.is_match("café"));
+    }
+
+    #[test]
+    fn test_build_regex_line() {
+        // -xの場合は行全体にマッチする必要がある
+        let re = build_regex("cat", false, false, true).unwrap();
+        assert!(re.is_match("cat"));
+        assert!(re.is_match("cat\n"));
+        assert!(!re.is_match("the cat\n"));
+        assert!(!re.is_match("cats\n"));
+
+        // -iと組み合わせられ、-wより優先する
+        let re = build_regex("cat|the dog", true, true, true).unwrap();
+        assert!(re.is_match("CAT\n"));
+        assert!(re.is_match("The Dog\n"));
+        assert!(!re.is_match("the cat\n"));
+
+        // 空のパターンは空行だけにマッチする
+        let re = build_regex("", false, false, true).unwrap();
+        assert!(re.is_match("\n"));
+        assert!(!re.is_match("cat\n"));
+    }
+
+    #[test]
+    fn test_join_patterns() {
+        // どれかのパターンにマッチすればよい
+        let patterns = vec!["fox".to_string(), "^dog$".to_string()];
+        let re = build_regex(&join_patterns(&patterns), false, false, false).unwrap();
+        assert!(re.is_match("the fox"));
+        assert!(re.is_match("dog"));
+        assert!(!re.is_match("the dog"));
+
+        // -iと-wはすべてのパターンに適用される
+        let re = build_regex(&join_patterns(&patterns), true, true, false).unwrap();
+        assert!(re.is_match("THE FOX"));
+        assert!(!re.is_match("foxes"));
+
+        // パターンがなければどの行にもマッチしない
+        let re = build_regex(&join_patterns(&[]), false, false, false).unwrap();
+        assert!(!re.is_match(""));
+        assert!(!re.is_match("fox"));
+    }
+
+    #[test]
+    fn test_file_matches() {
+        let text = b"Lorem\nIpsum\r\nDOLOR";
+        let re = Regex::new("or").unwrap();
+
+        // 最初にマッチした行で読むのをやめるはず
+        let mut cursor = Cursor::new(&text);
+        assert!(file_matches(&mut cursor, &re, false).unwrap());
+        assert_eq!(cursor.position(), 6);
+
+        // マッチしない場合は最後まで読むはず
+        let re = Regex::new("xyz").unwrap();
+        let mut cursor = Cursor::new(&text);
+        assert!(!file_matches(&mut cursor, &re, false).unwrap());
+        assert_eq!(cursor.position(), text.len() as u64);
+
+        // -vの場合はマッチしない行があればtrue
+        assert!(file_matches(Cursor::new(&text), &re, true).unwrap());
+
+        // UTF-8として正しくないバイトがあってもエラーにしない
+        let text = b"foo\0\xff\xfe bar\n";
+        let re = Regex::new("bar").unwrap();
+        assert!(file_matches(Cursor::new(&text), &re, false).unwrap());
+    }
+
+    #[test]
+    fn test_find_lines_max_count() {
+        let text = "a1\na2\nb\na3\na4\n";
+        let re = Regex::new("a").unwrap();
+
+        // 指定した件数だけを返し、残りは読まないはず
+        let mut cursor = Cursor::new(text);
+        let matches = find_lines(&mut cursor, &re, false, 0, 0, Some(2)).unwrap();
+        let line_nums: Vec<usize> = matches.iter().map(|line| line.num).collect();
+        assert_eq!(line_nums, vec![1, 2]);
+        assert_eq!(cursor.position(), 6);
+
+        // マッチを反転させた場合も反転後の件数で数える
+        let matches = find_lines(Cursor::new(text), &re, true, 0, 0, Some(1)).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].num, 3);
+
+        // 後ろの文脈行は件数に達した後も表示する
+        let matches = find_lines(Cursor::new(text), &re, false, 0, 1, Some(1)).unwrap();
+        let lines: Vec<(usize, bool)> = matches.iter().map(|l| (l.num, l.is_match)).collect();
+        assert_eq!(lines, vec![(1, true), (2, false)]);
+    }
+
+    fn context_lines(before: usize, after: usize) -> Vec<(usize, bool)> {
+        let text = (1..=10)
+            .map(|n| format!("line {}\n", n))
+            .collect::<String>();
+        let re = Regex::new(r"line [37]\b").unwrap();
+        find_lines(Cursor::new(text), &re, false, before, after, None)
+            .unwrap()
+            .iter()
+            .map(|line| (line.num, line.is_match))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_lines_context() {
+        // -A1はマッチした行の次の1行を含む
+        assert_eq!(
+            context_lines(0, 1),
+            vec![(3, true), (4, false), (7, true), (8, false)]
+        );
+
+        // -B1はマッチした行の前の1行を含む
+        assert_eq!(
+            context_lines(1, 0),
+            vec![(2, false), (3, true), (6, false), (7, true)]
+        );
+
+        // 文脈が重なる場合は同じ行を重複させずに1つのグループにまとめる
+        assert_eq!(
+            context_lines(2, 2),
+            vec![
+                (1, false),
+                (2, false),
+                (3, true),
+                (4, false),
+                (5, false),
+                (6, false),
+                (7, true),
+                (8, false),
+                (9, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let re = Regex::new("o").unwrap();
+        assert_eq!(
+            highlight(&re, "foo\n"),
+            "f\x1b[01;31m\x1b[Ko\x1b[m\x1b[K\x1b[01;31m\x1b[Ko\x1b[m\x1b[K\n"
+        );
+
+        // マッチしない行や空のマッチはそのまま
+        assert_eq!(highlight(&re, "bar\n"), "bar\n");
+        let re = Regex::new("x*").unwrap();
+        assert_eq!(highlight(&re, "bar\n"), "bar\n");
+    }
+
+    #[test]
+    fn test_find_parts() {
+        let re = Regex::new("ca[a-z]").unwrap();
+        let line = Line {
+            num: 1,
+            offset: 0,
+            text: "the cat sat in a car\n".to_string(),
+            is_match: true,
+        };
+
+        // 1行に2つマッチする箇所があれば2つとも返す
+        assert_eq!(find_parts(&re, &line), vec![(4, "cat"), (17, "car")]);
+
+        // 空のマッチは返さない
+        let re = Regex::new("x*").unwrap();
+        assert!(find_parts(&re, &line).is_empty());
+    }
+
+    #[test]
+    fn test_json_records() {
+        let re = Regex::new("fox").unwrap();
+        let line = Line {
+            num: 1,
+            offset: 0,
+            text: "The quick brown fox jumps over the lazy dog.\n".to_string(),
+            is_match: true,
+        };
+
+        let records = json_records("fox.txt", &re, &line);
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].to_string(),
+            r#"{"file":"fox.txt","line":"The quick brown fox jumps over the lazy dog.","line_number":1,"match_end":19,"match_start":16}"#
+        );
     }
 }
@@ -1,6 +1,9 @@
 fn main() {
-    if let Err(e) = grepr::get_args().and_then(grepr::run) {
-        eprintln!("Application error: {}", e);
-        std::process::exit(1);
+    match grepr::get_args().and_then(grepr::run) {
+        Err(e) => {
+            eprintln!("Application error: {}", e);
+            std::process::exit(2);
+        }
+        Ok(outcome) => std::process::exit(outcome.exit_code()),
     }
 }
@@ -8,10 +8,14 @@ use sys_info::os_type;
 
 const PRG: &str = "grepr";
 const BUSTLE: &str = "tests/inputs/bustle.txt";
+const PATTERNS: &str = "tests/patterns/fox_house.txt";
+const BUSTLE_GZ: &str = "tests/gzip/bustle.txt.gz";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const NOBODY: &str = "tests/inputs/nobody.txt";
 const INPUTS_DIR: &str = "tests/inputs";
+const BINARY: &str = "tests/binary/fox.bin";
+const INVALID_UTF8: &str = "tests/binary/invalid_utf8.bin";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -49,6 +53,34 @@ fn dies_bad_pattern() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn exit_code_match() -> Result<()> {
+    // GNU grepと同じく、マッチすれば0、しなければ1、エラーなら2
+    Command::cargo_bin(PRG)?.args(["fox", FOX]).assert().code(0);
+    Command::cargo_bin(PRG)?
+        .args(["nobody", FOX])
+        .assert()
+        .code(1);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn exit_code_error() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["*foo", FOX])
+        .assert()
+        .code(2);
+
+    // マッチしたファイルがあってもエラーがあれば2
+    Command::cargo_bin(PRG)?
+        .args(["fox", FOX, &gen_bad_file()])
+        .assert()
+        .code(2);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn warns_bad_file() -> Result<()> {
@@ -63,6 +95,11 @@ fn warns_bad_file() -> Result<()> {
 
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    run_with_code(args, expected_file, 0)
+}
+
+// --------------------------------------------------
+fn run_with_code(args: &[&str], expected_file: &str, code: i32) -> Result<()> {
     let windows_file = format!("{expected_file}.windows");
     let expected_file = if os_type().unwrap() == "Windows" && Path::new(&windows_file).is_file() {
         &windows_file
@@ -72,7 +109,7 @@ fn run(args: &[&str], expected_file: &str) -> Result<()> {
 
     let expected = fs::read_to_string(expected_file)?;
     let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
-    assert!(output.status.success());
+    assert_eq!(output.status.code(), Some(code));
 
     let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
     assert_eq!(stdout, expected);
@@ -82,7 +119,8 @@ fn run(args: &[&str], expected_file: &str) -> Result<()> {
 // --------------------------------------------------
 #[test]
 fn empty_file() -> Result<()> {
-    run(&["foo", EMPTY], "tests/expected/empty.foo")
+    // マッチしない場合は終了コード1
+    run_with_code(&["foo", EMPTY], "tests/expected/empty.foo", 1)
 }
 
 // --------------------------------------------------
@@ -100,6 +138,29 @@ fn bustle_capitalized() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn bustle_gz() -> Result<()> {
+    // gzipで圧縮されたファイルは展開して検索する
+    run(
+        &["The", BUSTLE_GZ],
+        "tests/expected/bustle.txt.the.capitalized",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_gz_stdin() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/bustle.txt.the.capitalized")?;
+    Command::cargo_bin(PRG)?
+        .arg("The")
+        .write_stdin(fs::read(BUSTLE_GZ)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn bustle_lowercase() -> Result<()> {
@@ -118,7 +179,7 @@ fn bustle_insensitive() -> Result<()> {
 // --------------------------------------------------
 #[test]
 fn nobody() -> Result<()> {
-    run(&["nobody", NOBODY], "tests/expected/nobody.txt")
+    run_with_code(&["nobody", NOBODY], "tests/expected/nobody.txt", 1)
 }
 
 // --------------------------------------------------
@@ -196,7 +257,11 @@ fn insensitive_count() -> Result<()> {
 // --------------------------------------------------
 #[test]
 fn nobody_count() -> Result<()> {
-    run(&["-c", "nobody", NOBODY], "tests/expected/nobody.txt.count")
+    run_with_code(
+        &["-c", "nobody", NOBODY],
+        "tests/expected/nobody.txt.count",
+        1,
+    )
 }
 
 // --------------------------------------------------
@@ -281,3 +346,533 @@ fn stdin_insensitive_count() -> Result<()> {
     assert_eq!(stdout, expected);
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn bustle_line_number() -> Result<()> {
+    run(
+        &["-n", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.line_number",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_line_number() -> Result<()> {
+    run(
+        &["--line-number", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.line_number",
+    )
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input: &str, expected: &str) -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file() -> Result<()> {
+    // ファイルのどれかのパターンにマッチする行を表示し、空行は飛ばす
+    run_stdin(
+        &["-f", PATTERNS],
+        "the fox\nthe cat\nthe house\n",
+        "the fox\nthe house\n",
+    )?;
+
+    // -fの場合は最初の位置引数もファイルとして扱う
+    run(
+        &["--file", PATTERNS, BUSTLE, FOX],
+        "tests/expected/patterns.fox_house.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_byte_offset() -> Result<()> {
+    // 行の開始位置はマルチバイト文字を含む前の行の長さも数える
+    run(
+        &["-b", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.byte_offset",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_only_matching_byte_offset() -> Result<()> {
+    // -oの場合はマッチした箇所そのものの位置を表示する
+    run(
+        &["-obi", "the", BUSTLE],
+        "tests/expected/bustle.txt.the.insensitive.only_matching.byte_offset",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn byte_offset_context() -> Result<()> {
+    // 文脈行にも位置を表示し、「-」で区切る
+    run_stdin(
+        &["-n", "-b", "-A1", "fox"],
+        "a\nthe fox\nlonger line\nend\n",
+        "2:2:the fox\n3-10-longer line\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp() -> Result<()> {
+    // 行全体がパターンにマッチする行だけを表示する
+    run_stdin(&["-x", "cat"], "cat\nthe cat\ncats\n", "cat\n")?;
+    run_stdin(
+        &["--line-regexp", "-i", "-w", "cat"],
+        "CAT\nthe cat\n",
+        "CAT\n",
+    )?;
+
+    // 強調するのは改行を除いた行全体
+    run_stdin(
+        &["-x", "--color=always", "cat"],
+        "cat\n",
+        "\x1b[01;31m\x1b[Kcat\x1b[m\x1b[K\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "tests/patterns/does-not-exist", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("tests/patterns/does-not-exist:"));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn ten_lines() -> String {
+    (1..=10).map(|n| format!("line {n}\n")).collect()
+}
+
+// --------------------------------------------------
+#[test]
+fn after_context() -> Result<()> {
+    run_stdin(
+        &["-A1", r"line [37]\b"],
+        &ten_lines(),
+        "line 3\nline 4\n--\nline 7\nline 8\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn before_context_line_number() -> Result<()> {
+    run_stdin(
+        &["-n", "-B1", r"line [37]\b"],
+        &ten_lines(),
+        "2-line 2\n3:line 3\n--\n6-line 6\n7:line 7\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn context_merges_overlapping_groups() -> Result<()> {
+    run_stdin(
+        &["-C2", r"line [37]\b"],
+        &ten_lines(),
+        "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8\nline 9\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching() -> Result<()> {
+    run_stdin(
+        &["-o", "ca[a-z]"],
+        "the cat sat in a car\nno match here\n",
+        "cat\ncar\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_line_number() -> Result<()> {
+    run_stdin(
+        &["-on", "ca[a-z]"],
+        "the cat sat in a car\nno match here\nscat\n",
+        "1:cat\n1:car\n3:cat\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_count() -> Result<()> {
+    run_stdin(
+        &["-oc", "ca[a-z]"],
+        "the cat sat in a car\nno match here\nscat\n",
+        "3\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn json() -> Result<()> {
+    run(&["--json", "fox", FOX], "tests/expected/fox.txt.json")
+}
+
+// --------------------------------------------------
+fn run_sorted(args: &[&str], expected: &[&str]) -> Result<()> {
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    let mut lines: Vec<String> = stdout.lines().map(|line| line.replace('\\', "/")).collect();
+    lines.sort();
+    assert_eq!(lines, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches_recursive() -> Result<()> {
+    run_sorted(
+        &["-rl", "The", INPUTS_DIR],
+        &[
+            "tests/inputs/bustle.txt",
+            "tests/inputs/fox.txt",
+            "tests/inputs/nobody.txt",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files_without_match_recursive() -> Result<()> {
+    run_sorted(&["-rL", "The", INPUTS_DIR], &["tests/inputs/empty.txt"])
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_with_and_without_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-L", "The", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp() -> Result<()> {
+    run_stdin(&["-w", "cat"], "the cat\ncategory\nconcat\n", "the cat\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_insensitive() -> Result<()> {
+    run_stdin(&["-wi", "cat"], "the CAT\nCATEGORY\n", "the CAT\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn count_no_match() -> Result<()> {
+    // マッチしないファイルも飛ばさずに0を表示する
+    Command::cargo_bin(PRG)?
+        .args(["-c", "nobody", FOX, BUSTLE])
+        .assert()
+        .code(1)
+        .stdout(format!("{FOX}:0\n{BUSTLE}:0\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn count_files_with_and_without_match() -> Result<()> {
+    // -l/-Lと一緒に指定した場合は件数ではなくファイル名だけを表示する
+    Command::cargo_bin(PRG)?
+        .args(["-c", "-L", "Nobody", FOX, NOBODY, BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!("{FOX}\n{BUSTLE}\n"));
+
+    Command::cargo_bin(PRG)?
+        .args(["-c", "-l", "Nobody", FOX, NOBODY, BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!("{NOBODY}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count() -> Result<()> {
+    run(
+        &["-m", "1", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.m1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_count() -> Result<()> {
+    run_stdin(
+        &["-c", "--max-count", "2", "The"],
+        &fs::read_to_string(BUSTLE)?,
+        "2\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include_exclude() -> Result<()> {
+    run_sorted(
+        &[
+            "-rl",
+            "--include",
+            "*.rs",
+            "--include",
+            "*.lock",
+            "--exclude",
+            "util.rs",
+            "hello",
+            "tests/mixed",
+        ],
+        &["tests/mixed/deps.lock", "tests/mixed/hello.rs"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn with_filename_single_file() -> Result<()> {
+    run(
+        &["-H", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.with_filename",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn no_filename_multiple_files() -> Result<()> {
+    run(
+        &["-h", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.no_filename",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stats() -> Result<()> {
+    // 集計は通常の出力のあとにSTDERRへ表示する
+    let expected =
+        r"^files searched: 4, files with matches: 3, matches: 5, elapsed: \d+\.\d{3}s\n$";
+    Command::cargo_bin(PRG)?
+        .args(["--stats", "-c", "-r", "The", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tests/inputs/bustle.txt:3"))
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_accents() -> Result<()> {
+    // -wはアクセント付きの文字も単語の一部とみなす
+    run_stdin(&["-w", "élan"], "un élan.\nélans\n", "un élan.\n")?;
+    run_stdin(&["-w", "caf"], "café\ncaf é\n", "caf é\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn passthru_color() -> Result<()> {
+    // マッチしない行もそのまま表示し、マッチした箇所だけを強調する
+    run_stdin(
+        &["--passthru", "--color=always", "fox"],
+        "the fox\nthe dog\n",
+        "the \x1b[01;31m\x1b[Kfox\x1b[m\x1b[K\nthe dog\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always() -> Result<()> {
+    // 1行に複数のマッチがあればすべて強調する
+    run_stdin(
+        &["--color=always", "o"],
+        "the fox\nthe dog\nfoo\n",
+        "the f\x1b[01;31m\x1b[Ko\x1b[m\x1b[Kx\n\
+         the d\x1b[01;31m\x1b[Ko\x1b[m\x1b[Kg\n\
+         f\x1b[01;31m\x1b[Ko\x1b[m\x1b[K\x1b[01;31m\x1b[Ko\x1b[m\x1b[K\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn color_never_and_auto() -> Result<()> {
+    run_stdin(&["--color=never", "fox"], "the fox\n", "the fox\n")?;
+
+    // テストの標準出力は端末ではないので、autoでは強調しない
+    run_stdin(&["--color=auto", "fox"], "the fox\n", "the fox\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn passthru_line_number() -> Result<()> {
+    run_stdin(
+        &["--passthru", "-n", "dog"],
+        "the fox\nthe dog\n",
+        "1-the fox\n2:the dog\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_matches() -> Result<()> {
+    // NULを含むファイルはマッチした行の代わりにメッセージを表示する
+    Command::cargo_bin(PRG)?
+        .args(["fox", BINARY])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {} matches\n", BINARY));
+
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=text", "fox", BINARY])
+        .assert()
+        .success()
+        .stdout("the fox\0jumps\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_invalid_utf8() -> Result<()> {
+    // UTF-8として正しくないバイトを含むバイナリファイルも読み込みエラーにしない
+    Command::cargo_bin(PRG)?
+        .args(["foo", INVALID_UTF8])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {} matches\n", INVALID_UTF8));
+
+    Command::cargo_bin(PRG)?
+        .args(["-l", "foo", INVALID_UTF8])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", INVALID_UTF8));
+
+    Command::cargo_bin(PRG)?
+        .args(["-c", "foo", INVALID_UTF8])
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-c", "xyz", INVALID_UTF8])
+        .assert()
+        .code(1)
+        .stdout("0\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_without_match() -> Result<()> {
+    // -Iの場合はバイナリファイルをマッチしないファイルとして扱う
+    for flag in ["-I", "--binary-files=without-match"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "fox", BINARY, FOX])
+            .assert()
+            .success()
+            .stdout(format!(
+                "{}:The quick brown fox jumps over the lazy dog.\n",
+                FOX
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn dereference_recursive() -> Result<()> {
+    use std::os::unix::fs::symlink;
+
+    // search/linkはsearchの外にあるtargetを指す
+    let dir = tempfile::tempdir()?;
+    fs::create_dir(dir.path().join("target"))?;
+    fs::write(dir.path().join("target/fox.txt"), "the fox\n")?;
+    fs::create_dir(dir.path().join("search"))?;
+    symlink("../target", dir.path().join("search/link"))?;
+    let search = dir.path().join("search");
+
+    // -rはシンボリックリンクをたどらない
+    Command::cargo_bin(PRG)?
+        .args(["-r", "fox"])
+        .arg(&search)
+        .assert()
+        .code(1)
+        .stdout("");
+
+    // -Rはたどる
+    Command::cargo_bin(PRG)?
+        .args(["-R", "-H", "fox"])
+        .arg(&search)
+        .assert()
+        .success()
+        .stdout(format!("{}/link/fox.txt:the fox\n", search.display()));
+
+    // ループするリンクはエラーを表示して飛ばし、ほかのファイルは検索する
+    symlink(".", search.join("loop"))?;
+    Command::cargo_bin(PRG)?
+        .args(["--dereference-recursive", "-H", "fox"])
+        .arg(&search)
+        .assert()
+        .code(2)
+        .stdout(format!("{}/link/fox.txt:the fox\n", search.display()))
+        .stderr(predicate::str::contains("loop"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_many_files() -> Result<()> {
+    // 出力をバッファしても、たくさんのファイルのマッチと件数をすべて表示する
+    let dir = tempfile::tempdir()?;
+    for i in 0..500 {
+        fs::write(
+            dir.path().join(format!("{:03}.txt", i)),
+            format!("line {i}\nfox {i}\n"),
+        )?;
+    }
+    let root = dir.path().display().to_string();
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-rn", "fox", &root])
+        .output()?;
+    assert!(output.status.success());
+    let mut lines: Vec<String> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(String::from)
+        .collect();
+    lines.sort();
+    let expected: Vec<String> = (0..500)
+        .map(|i| format!("{root}/{:03}.txt:2:fox {i}", i))
+        .collect();
+    assert_eq!(lines, expected);
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-rc", "line", &root])
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout.lines().count(), 500);
+    assert!(stdout.lines().all(|line| line.ends_with(".txt:1")));
+    Ok(())
+}
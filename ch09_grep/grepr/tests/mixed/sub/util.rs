@@ -0,0 +1 @@
+fn hello_util() {}
@@ -1,13 +1,8 @@
 use crate::Column::*;
 use clap::{App, Arg};
-use std::cmp::Ordering::*;
-use std::{
-    error::Error,
-    fs::File,
-    io::{self, BufRead, BufReader},
-};
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
+use common::{open, MyResult};
+use std::cmp::Ordering::{self, *};
+use std::io::BufRead;
 
 #[derive(Debug)]
 pub struct Config {
@@ -18,6 +13,9 @@ pub struct Config {
     show_col3: bool,
     insensitive: bool,
     delimiter: String,
+    check_order: bool,
+    total: bool,
+    zero_terminated: bool,
 }
 
 enum Column<'a> {
@@ -78,6 +76,25 @@ pub fn get_args() -> MyResult<Config> {
                 .default_value("\t")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("check_order")
+                .long("check-order")
+                .help("Check that the input is correctly sorted")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("total")
+                .long("total")
+                .help("Output a summary of the number of lines in each column")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline")
+                .takes_value(false),
+        )
         .get_matches();
 
     Ok(Config {
@@ -87,10 +104,38 @@ pub fn get_args() -> MyResult<Config> {
         show_col2: !matches.is_present("suppress2"),
         show_col3: !matches.is_present("suppress3"),
         insensitive: matches.is_present("insensitive"),
-        delimiter: matches.value_of_lossy("delimiter").unwrap().to_string(),
+        delimiter: parse_delimiter(&matches.value_of_lossy("delimiter").unwrap()),
+        check_order: matches.is_present("check_order"),
+        total: matches.is_present("total"),
+        zero_terminated: matches.is_present("zero_terminated"),
     })
 }
 
+// シェルが展開しない「\t」などのエスケープシーケンスを文字に置き換える。
+// それ以外の「\」はそのまま残す
+fn parse_delimiter(val: &str) -> String {
+    let mut delimiter = String::new();
+    let mut chars = val.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            delimiter.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => delimiter.push('\t'),
+            Some('n') => delimiter.push('\n'),
+            Some('0') => delimiter.push('\0'),
+            Some('\\') => delimiter.push('\\'),
+            Some(other) => {
+                delimiter.push('\\');
+                delimiter.push(other);
+            }
+            None => delimiter.push('\\'),
+        }
+    }
+    delimiter
+}
+
 pub fn run(config: Config) -> MyResult<()> {
     let file1 = &config.file1;
     let file2 = &config.file2;
@@ -99,91 +144,181 @@ pub fn run(config: Config) -> MyResult<()> {
         return Err(From::from("Both input files cannot be STDIN (\"-\")"));
     }
 
-    let case = |line: String| {
-        if config.insensitive {
-            line.to_lowercase()
-        } else {
-            line
-        }
+    // -1/-2/-3で非表示にした列も件数には含める
+    let mut totals = [0; 3];
+    let mut print = |col: Column| {
+        let i = match col {
+            Col1(_) => 0,
+            Col2(_) => 1,
+            Col3(_) => 2,
+        };
+        totals[i] += 1;
+        print_row(&config, col);
     };
 
-    let print = |col: Column| {
-        let mut columns = vec![];
-        match col {
-            Col1(val) => {
-                if config.show_col1 {
-                    columns.push(val);
-                }
-            }
-            Col2(val) => {
-                if config.show_col2 {
-                    if config.show_col1 {
-                        columns.push("");
-                    }
-                    columns.push(val);
-                }
-            }
-            Col3(val) => {
-                if config.show_col3 {
-                    if config.show_col1 {
-                        columns.push("");
-                    }
-                    if config.show_col2 {
-                        columns.push("");
-                    }
-                    columns.push(val);
-                }
-            }
-        }
-
-        if !columns.is_empty() {
-            println!("{}", columns.join(&config.delimiter));
-        }
-    };
+    let open = |filename: &str| open(filename).map_err(|e| format!("{}: {}", filename, e));
+    let mut lines1 = records(open(file1)?, config.zero_terminated);
+    let mut lines2 = records(open(file2)?, config.zero_terminated);
 
-    let mut lines1 = open(file1)?.lines().map_while(Result::ok).map(case);
-    let mut lines2 = open(file2)?.lines().map_while(Result::ok).map(case);
+    let mut prev1 = None;
+    let mut prev2 = None;
+    let mut next1 = || check_order(&config, 1, &mut prev1, lines1.next());
+    let mut next2 = || check_order(&config, 2, &mut prev2, lines2.next());
 
-    let mut line1 = lines1.next();
-    let mut line2 = lines2.next();
+    let mut line1 = next1()?;
+    let mut line2 = next2()?;
 
     while line1.is_some() || line2.is_some() {
         match (&line1, &line2) {
-            (Some(val1), Some(val2)) => match val1.cmp(val2) {
+            (Some(val1), Some(val2)) => match compare(val1, val2, config.insensitive) {
                 Equal => {
                     print(Col3(val1));
-                    line1 = lines1.next();
-                    line2 = lines2.next();
+                    line1 = next1()?;
+                    line2 = next2()?;
                 }
                 Less => {
                     print(Col1(val1));
-                    line1 = lines1.next();
+                    line1 = next1()?;
                 }
                 Greater => {
                     print(Col2(val2));
-                    line2 = lines2.next();
+                    line2 = next2()?;
                 }
             },
             (Some(val1), None) => {
                 print(Col1(val1));
-                line1 = lines1.next();
+                line1 = next1()?;
             }
             (None, Some(val2)) => {
                 print(Col2(val2));
-                line2 = lines2.next();
+                line2 = next2()?;
             }
             _ => (),
         }
     }
 
+    if config.total {
+        print!(
+            "{}{}",
+            [
+                totals[0].to_string(),
+                totals[1].to_string(),
+                totals[2].to_string(),
+                "total".to_string(),
+            ]
+            .join(&config.delimiter),
+            terminator(&config)
+        );
+    }
+
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(
-            File::open(filename).map_err(|e| format!("{}: {}", filename, e))?,
-        ))),
+// 1行分を表示する。非表示の列より右の列は空の列で位置をそろえる
+fn print_row(config: &Config, col: Column) {
+    let mut columns = vec![];
+    match col {
+        Col1(val) => {
+            if config.show_col1 {
+                columns.push(val);
+            }
+        }
+        Col2(val) => {
+            if config.show_col2 {
+                if config.show_col1 {
+                    columns.push("");
+                }
+                columns.push(val);
+            }
+        }
+        Col3(val) => {
+            if config.show_col3 {
+                if config.show_col1 {
+                    columns.push("");
+                }
+                if config.show_col2 {
+                    columns.push("");
+                }
+                columns.push(val);
+            }
+        }
+    }
+
+    if !columns.is_empty() {
+        print!("{}{}", columns.join(&config.delimiter), terminator(config));
+    }
+}
+
+// -iの場合は小文字に揃えた値で比較し、表示には元の行を使う
+fn compare(line1: &str, line2: &str, insensitive: bool) -> Ordering {
+    if insensitive {
+        line1.to_lowercase().cmp(&line2.to_lowercase())
+    } else {
+        line1.cmp(line2)
+    }
+}
+
+// 直前の行より小さい行が現れたらソートされていないとみなす
+fn check_order(
+    config: &Config,
+    file_num: usize,
+    prev: &mut Option<String>,
+    line: Option<String>,
+) -> MyResult<Option<String>> {
+    if config.check_order {
+        if let (Some(prev), Some(line)) = (prev.as_ref(), line.as_ref()) {
+            if compare(line, prev, config.insensitive) == Less {
+                return Err(From::from(format!(
+                    "comm: file {} is not in sorted order",
+                    file_num
+                )));
+            }
+        }
+        prev.clone_from(&line);
+    }
+    Ok(line)
+}
+
+// -zの場合はNULで区切られたレコードとして読む
+fn records(file: Box<dyn BufRead>, zero_terminated: bool) -> Box<dyn Iterator<Item = String>> {
+    if zero_terminated {
+        Box::new(
+            file.split(b'\0')
+                .map_while(Result::ok)
+                .map(|record| String::from_utf8_lossy(&record).into_owned()),
+        )
+    } else {
+        Box::new(file.lines().map_while(Result::ok))
+    }
+}
+
+fn terminator(config: &Config) -> char {
+    if config.zero_terminated {
+        '\0'
+    } else {
+        '\n'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_delimiter;
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(r"\t"), "\t");
+        assert_eq!(parse_delimiter(r"\n"), "\n");
+        assert_eq!(parse_delimiter(r"\0"), "\0");
+        assert_eq!(parse_delimiter(r"\\"), "\\");
+
+        // 展開済みのタブや複数文字の区切りはそのまま使う
+        assert_eq!(parse_delimiter("\t"), "\t");
+        assert_eq!(parse_delimiter("::"), "::");
+        assert_eq!(parse_delimiter(r"a\tb"), "a\tb");
+
+        // 知らないエスケープや末尾の「\」は文字どおりに扱う
+        assert_eq!(parse_delimiter(r"\x"), r"\x");
+        assert_eq!(parse_delimiter(r"a\"), r"a\");
+        assert_eq!(parse_delimiter(r"\\t"), r"\t");
     }
 }
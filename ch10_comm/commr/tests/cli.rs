@@ -338,3 +338,147 @@ fn file1_file2_123_delim() -> Result<()> {
 fn blank_file1() -> Result<()> {
     run(&[BLANK, FILE1], "tests/expected/blank_file1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn dies_check_order() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", FILE1, "tests/inputs/unsorted.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "comm: file 2 is not in sorted order",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn check_order_insensitive() -> Result<()> {
+    let mixed_case = "tests/inputs/mixed_case.txt";
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", mixed_case, FILE1])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "comm: file 1 is not in sorted order",
+        ));
+
+    // -iの場合は大文字小文字を区別せずに順序を確認する
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", "-i", mixed_case, FILE1])
+        .assert()
+        .success();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn unsorted_without_check_order() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([FILE1, "tests/inputs/unsorted.txt"])
+        .assert()
+        .success();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn apple_upper_apple_lower_i() -> Result<()> {
+    // 比較は大文字小文字を区別しないが、表示は1つ目のファイルの行のまま
+    run(
+        &[
+            "-i",
+            "tests/inputs/apple_upper.txt",
+            "tests/inputs/apple_lower.txt",
+        ],
+        "tests/expected/apple_upper_apple_lower.i.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn file1_file2_12_total() -> Result<()> {
+    run(
+        &[FILE1, FILE2, "-12", "--total"],
+        "tests/expected/file1_file2.12.total.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn file1_file2_2_total_delim() -> Result<()> {
+    run(
+        &[FILE1, FILE2, "-2", "--total", "-d", ":"],
+        "tests/expected/file1_file2.2.total.delim.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn cities1_cities2_sorted() -> Result<()> {
+    // 1行ずつ表示してもGNU commと同じ出力になる
+    run(
+        &[
+            "tests/inputs/cities1_sorted.txt",
+            "tests/inputs/cities2_sorted.txt",
+        ],
+        "tests/expected/cities1_cities2_sorted.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn cities1_cities2_sorted_2_delim() -> Result<()> {
+    run(
+        &[
+            "-2",
+            "-d",
+            ":",
+            "tests/inputs/cities1_sorted.txt",
+            "tests/inputs/cities2_sorted.txt",
+        ],
+        "tests/expected/cities1_cities2_sorted.2.delim.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_file1_three_columns() -> Result<()> {
+    // 標準入力から読んだ場合もファイルと同じ3列の出力になる
+    run_stdin(&["-", FILE2], FILE1, "tests/expected/file1_file2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_file2_three_columns() -> Result<()> {
+    run_stdin(&[FILE1, "-"], FILE2, "tests/expected/file1_file2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn file1_file2_zero_terminated() -> Result<()> {
+    run(
+        &[
+            "-z",
+            "tests/inputs/file1.nul.txt",
+            "tests/inputs/file2.nul.txt",
+        ],
+        "tests/expected/file1_file2.z.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn file1_file2_1_zero_terminated_total() -> Result<()> {
+    run(
+        &[
+            "-1",
+            "--zero-terminated",
+            "--total",
+            "tests/inputs/file1.nul.txt",
+            "tests/inputs/file2.nul.txt",
+        ],
+        "tests/expected/file1_file2.1.z.total.out",
+    )
+}
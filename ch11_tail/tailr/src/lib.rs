@@ -1,16 +1,24 @@
 use crate::TakeValue::*;
 use clap::{App, Arg};
+use common::MyResult;
 use once_cell::sync::OnceCell;
 use regex::Regex;
 use std::{
-    error::Error,
-    fs::File,
-    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    mem, thread,
+    time::Duration,
 };
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 
 static NUM_RE: OnceCell<Regex> = OnceCell::new();
+static BYTES_RE: OnceCell<Regex> = OnceCell::new();
+
+trait Input: BufRead + Seek {}
+impl<T: BufRead + Seek> Input for T {}
 
 #[derive(Debug, PartialEq)]
 enum TakeValue {
@@ -23,7 +31,24 @@ pub struct Config {
     files: Vec<String>,
     lines: TakeValue,
     bytes: Option<TakeValue>,
+    lines_then_bytes: bool,
     quiet: bool,
+    zero_terminated: bool,
+    follow: bool,
+    follow_name: bool,
+    sleep_interval: Duration,
+}
+
+/// -fで追跡中のファイルの状態
+#[derive(Debug)]
+struct Followed<'a> {
+    filename: &'a str,
+    // 表示済みのバイト数
+    pos: u64,
+    // -Fでファイルが置き換えられたことを検出するためのinode番号。Unix以外では常にNone
+    ino: Option<u64>,
+    // -Fで見つからないことを報告済みなら、次に現れたときに先頭から表示する
+    missing: bool,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -55,12 +80,45 @@ pub fn get_args() -> MyResult<Config> {
                 .conflicts_with("lines")
                 .help("Number of bytes"),
         )
+        .arg(
+            Arg::with_name("lines_then_bytes")
+                .long("lines-then-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .conflicts_with("bytes")
+                .help("Number of bytes to take from the end of the selected lines"),
+        )
         .arg(
             Arg::with_name("quiet")
                 .short("q")
                 .long("quiet")
                 .help("Suppress headers"),
         )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline"),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .short("f")
+                .long("follow")
+                .help("Output appended data as the file grows"),
+        )
+        .arg(
+            Arg::with_name("follow_name")
+                .short("F")
+                .help("Follow the file by name, reopening it when it is rotated"),
+        )
+        .arg(
+            Arg::with_name("sleep_interval")
+                .short("s")
+                .long("sleep-interval")
+                .value_name("N")
+                .help("With -f, sleep for N seconds between checks")
+                .default_value("1.0"),
+        )
         .get_matches();
 
     let lines = matches
@@ -71,46 +129,242 @@ pub fn get_args() -> MyResult<Config> {
 
     let bytes = matches
         .value_of("bytes")
-        .map(parse_num)
+        .map(parse_bytes)
+        .transpose()
+        .map_err(|e| format!("illegal byte count -- {}", e))?;
+
+    // GNU tailと同じく-nと-cは同時に使えないので、両方を使う場合は別の引数でバイト数を指定する
+    let lines_then_bytes = matches
+        .value_of("lines_then_bytes")
+        .map(parse_bytes)
         .transpose()
         .map_err(|e| format!("illegal byte count -- {}", e))?;
 
+    let sleep_interval = matches
+        .value_of("sleep_interval")
+        .map(parse_interval)
+        .transpose()?;
+
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
         lines: lines.unwrap(),
-        bytes,
+        bytes: bytes.or(lines_then_bytes),
+        lines_then_bytes: matches.is_present("lines_then_bytes"),
         quiet: matches.is_present("quiet"),
+        zero_terminated: matches.is_present("zero_terminated"),
+        follow: matches.is_present("follow") || matches.is_present("follow_name"),
+        follow_name: matches.is_present("follow_name"),
+        sleep_interval: sleep_interval.unwrap(),
     })
 }
 
 pub fn run(config: Config) -> MyResult<()> {
     let has_multple_files = config.files.len() > 1;
-
-    for (file_num, filename) in config.files.iter().enumerate() {
-        match File::open(filename) {
-            Err(e) => eprintln!("{}: {}", filename, e),
-            Ok(file) => {
-                if !config.quiet && has_multple_files {
-                    println!(
-                        "{}==> {} <==",
-                        if file_num > 0 { "\n" } else { "" },
+    let delimiter = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut followed = vec![];
+    // 開けなかったファイルにはヘッダーを表示しないので、2つ目以降のヘッダーの前にだけ空行を入れる
+    let mut printed_header = false;
+
+    for filename in &config.files {
+        let mut print_header = || {
+            if !config.quiet && has_multple_files {
+                // GNU tailと同じく標準入力は「standard input」と表示する
+                println!(
+                    "{}==> {} <==",
+                    if printed_header { "\n" } else { "" },
+                    if filename == "-" {
+                        "standard input"
+                    } else {
                         filename
-                    );
+                    }
+                );
+                printed_header = true;
+            }
+        };
+
+        // -nの場合はシークせずに1回だけ読むので、パイプからも読める
+        let total_bytes = match config.bytes {
+            Some(ref n) => match open(filename) {
+                Err(e) => Err(e),
+                Ok(mut file) => {
+                    print_header();
+                    let (total_lines, total_bytes) = count_lines_bytes(&mut file, delimiter)?;
+                    file.rewind()?;
+                    // --lines-then-bytesの場合は-nで選んだ行の範囲から-cで切り出す
+                    let offset = if config.lines_then_bytes {
+                        line_offset(&mut file, &config.lines, total_lines, delimiter)?
+                    } else {
+                        Some(0)
+                    };
+                    if let Some(offset) = offset {
+                        print_bytes(file, n, offset, total_bytes)?;
+                    }
+                    Ok(total_bytes as u64)
+                }
+            },
+            None => match common::open(filename) {
+                Err(e) => Err(e),
+                Ok(file) => {
+                    print_header();
+                    Ok(print_lines(file, &config.lines, delimiter, io::stdout())?)
+                }
+            },
+        };
+
+        match total_bytes {
+            Err(e) => {
+                eprintln!("{}: {}", filename, e);
+                // -Fの場合はあとから作成されたファイルも追跡する
+                if config.follow_name && filename != "-" {
+                    followed.push(Followed {
+                        filename,
+                        pos: 0,
+                        ino: None,
+                        missing: true,
+                    });
+                }
+            }
+            Ok(total_bytes) => {
+                // 表示したあとに追記された分は-fで表示する。標準入力は追跡しない
+                if filename != "-" {
+                    followed.push(Followed {
+                        filename,
+                        pos: total_bytes,
+                        ino: fs::metadata(filename).ok().and_then(|m| inode(&m)),
+                        missing: false,
+                    });
                 }
-
-                let (total_lines, total_bytes) = count_lines_bytes(filename)?;
-                let file = BufReader::new(file);
-                if let Some(ref n) = config.bytes {
-                    print_bytes(file, n, total_bytes)?;
-                } else {
-                    print_lines(file, &config.lines, total_lines)?;
-                };
             }
         }
     }
+
+    if config.follow {
+        // 標準入力のパイプは追跡しないので、標準入力だけなら-fでもそのまま終わる。
+        // 追跡できるファイルが1つもなければGNU tailと同じくエラーにする
+        if followed.is_empty() {
+            return if config.files.iter().all(|filename| filename == "-") {
+                Ok(())
+            } else {
+                Err(From::from("no files remaining"))
+            };
+        }
+        follow(
+            followed,
+            config.sleep_interval,
+            !config.quiet && has_multple_files,
+            config.follow_name,
+        )?;
+    }
     Ok(())
 }
 
+/// ファイルの末尾に追加されたデータを表示し続ける。
+/// 複数のファイルの場合は、表示するファイルが変わるたびにヘッダーを表示する。
+/// by_nameがtrue（-F）の場合は、ローテーションで置き換えられた新しいファイルを先頭から表示する。
+/// ファイルが一時的になくなっても終わらずに、再び作成されるのを待つ
+fn follow(
+    mut files: Vec<Followed>,
+    interval: Duration,
+    headers: bool,
+    by_name: bool,
+) -> MyResult<()> {
+    let mut last_shown = files.len().checked_sub(1);
+
+    loop {
+        thread::sleep(interval);
+        for (i, followed) in files.iter_mut().enumerate() {
+            let filename = followed.filename;
+            let metadata = match fs::metadata(filename) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    if by_name {
+                        report_missing(followed, &e);
+                    }
+                    continue;
+                }
+            };
+            let len = metadata.len();
+            let ino = inode(&metadata);
+            if by_name && followed.missing {
+                eprintln!("{}: file appeared; following new file", filename);
+                followed.missing = false;
+                followed.ino = ino;
+                followed.pos = 0;
+            } else if by_name && followed.ino != ino {
+                eprintln!("{}: file replaced; following new file", filename);
+                followed.ino = ino;
+                followed.pos = 0;
+            } else if len < followed.pos {
+                eprintln!("{}: file truncated", filename);
+                followed.pos = 0;
+            }
+            if len == followed.pos {
+                continue;
+            }
+
+            // ローテーションの途中などで、調べたあとに消えていることもある
+            let buffer = match read_range(filename, followed.pos, len - followed.pos) {
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    if by_name {
+                        report_missing(followed, &e);
+                    }
+                    continue;
+                }
+                Err(e) => return Err(From::from(format!("{}: {}", filename, e))),
+            };
+
+            if headers && last_shown != Some(i) {
+                println!("\n==> {} <==", filename);
+            }
+            // 複数バイトの文字が2回の書き込みに分かれることもあるので、読んだバイト列をそのまま書く
+            let mut stdout = io::stdout();
+            stdout.write_all(&buffer)?;
+            stdout.flush()?;
+
+            followed.pos = len;
+            last_shown = Some(i);
+        }
+    }
+}
+
+/// ファイルのposからlenバイトを読む
+fn read_range(filename: &str, pos: u64, len: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(filename)?;
+    file.seek(SeekFrom::Start(pos))?;
+    let mut buffer = Vec::new();
+    file.take(len).read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// -Fで追跡しているファイルが見つからなくなったことを1回だけ報告する
+fn report_missing(followed: &mut Followed, e: &io::Error) {
+    if !followed.missing {
+        eprintln!("{}: file has become inaccessible: {}", followed.filename, e);
+        followed.missing = true;
+    }
+}
+
+#[cfg(unix)]
+fn inode(metadata: &fs::Metadata) -> Option<u64> {
+    Some(metadata.ino())
+}
+
+/// inode番号がない環境では置き換えを検出できないので、
+/// ファイルが短くなったことだけで先頭から読み直す
+#[cfg(not(unix))]
+fn inode(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// 「0.5」のような秒数を解釈する。負の値はエラー
+fn parse_interval(val: &str) -> MyResult<Duration> {
+    val.parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| From::from(format!("invalid number of seconds: '{}'", val)))
+}
+
 fn parse_num(val: &str) -> MyResult<TakeValue> {
     let num_re = NUM_RE.get_or_init(|| Regex::new(r"^([+-])?(\d+)$").unwrap());
 
@@ -132,15 +386,47 @@ fn parse_num(val: &str) -> MyResult<TakeValue> {
     }
 }
 
-fn count_lines_bytes(filename: &str) -> MyResult<(i64, i64)> {
-    let mut file = BufReader::new(File::open(filename)?);
+// 標準入力はシークできないので、すべて読み込んでから扱う
+fn open(filename: &str) -> MyResult<Box<dyn Input>> {
+    match filename {
+        "-" => {
+            let mut buffer = Vec::new();
+            io::stdin().read_to_end(&mut buffer)?;
+            Ok(Box::new(Cursor::new(buffer)))
+        }
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+/// 「1K」のような単位付きのバイト数を解釈する。
+/// 符号の扱いは[`parse_num`]と同じ
+fn parse_bytes(val: &str) -> MyResult<TakeValue> {
+    let bytes_re = BYTES_RE.get_or_init(|| Regex::new(r"^([+-]?\d+)([kKmMgG])?$").unwrap());
+
+    let caps = bytes_re.captures(val).ok_or(val)?;
+    let multiplier: i64 = match caps.get(2).map(|m| m.as_str()) {
+        Some("k" | "K") => 1 << 10,
+        Some("m" | "M") => 1 << 20,
+        Some("g" | "G") => 1 << 30,
+        _ => 1,
+    };
+
+    match parse_num(&caps[1]).map_err(|_| val)? {
+        PlusZero => Ok(PlusZero),
+        TakeNum(num) => num
+            .checked_mul(multiplier)
+            .map(TakeNum)
+            .ok_or_else(|| From::from(val)),
+    }
+}
 
+fn count_lines_bytes(mut file: impl BufRead, delimiter: u8) -> MyResult<(i64, i64)> {
     let mut num_lines: i64 = 0;
     let mut num_bytes: i64 = 0;
     let mut buf = Vec::new();
 
     loop {
-        let bytes_read = file.read_until(b'\n', &mut buf)?;
+        let bytes_read = file.read_until(delimiter, &mut buf)?;
         if bytes_read == 0 {
             break;
         }
@@ -153,34 +439,88 @@ fn count_lines_bytes(filename: &str) -> MyResult<(i64, i64)> {
     Ok((num_lines, num_bytes))
 }
 
-fn print_lines(mut file: impl BufRead, num_lines: &TakeValue, total_lines: i64) -> MyResult<()> {
-    if let Some(start) = get_start_index(num_lines, total_lines) {
-        let mut buf = Vec::new();
-        let mut line_num = 0;
-        loop {
-            let bytes_read = file.read_until(b'\n', &mut buf)?;
-            if bytes_read == 0 {
-                break;
+/// 1回だけ読んで-nで選んだ行をoutに書き、読んだバイト数を返す。
+/// 末尾からN行の場合は最後のN行だけをリングバッファに保持する
+fn print_lines(
+    mut file: impl BufRead,
+    num_lines: &TakeValue,
+    delimiter: u8,
+    mut out: impl Write,
+) -> MyResult<u64> {
+    // 先頭から飛ばす行数と、末尾から保持する行数
+    let (skip, keep) = match *num_lines {
+        PlusZero => (0, None),
+        TakeNum(num) if num > 0 => (num as u64 - 1, None),
+        TakeNum(num) => (0, Some(num.unsigned_abs() as usize)),
+    };
+    // -nの値は巨大なこともあるので、あらかじめ確保せずに読んだ行の分だけ増やす
+    let mut last: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut buf = Vec::new();
+    let mut line_num = 0;
+    let mut total_bytes = 0;
+
+    loop {
+        let bytes_read = file.read_until(delimiter, &mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        total_bytes += bytes_read as u64;
+
+        match keep {
+            Some(0) => {}
+            Some(n) => {
+                if last.len() == n {
+                    last.pop_front();
+                }
+                last.push_back(mem::take(&mut buf));
             }
-            if line_num >= start {
-                print!("{}", String::from_utf8_lossy(&buf));
+            None if line_num >= skip => {
+                out.write_all(String::from_utf8_lossy(&buf).as_bytes())?;
             }
-            line_num += 1;
-            buf.clear();
+            None => {}
         }
+        line_num += 1;
+        buf.clear();
     }
-    Ok(())
+
+    for line in last {
+        out.write_all(String::from_utf8_lossy(&line).as_bytes())?;
+    }
+    Ok(total_bytes)
 }
 
+/// -nで表示する最初の行が始まるバイト位置を返す。表示する行がなければNone
+fn line_offset(
+    mut file: impl BufRead,
+    num_lines: &TakeValue,
+    total_lines: i64,
+    delimiter: u8,
+) -> MyResult<Option<u64>> {
+    let start = match get_start_index(num_lines, total_lines) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let mut buf = Vec::new();
+    let mut offset = 0;
+    for _ in 0..start {
+        offset += file.read_until(delimiter, &mut buf)? as u64;
+        buf.clear();
+    }
+    Ok(Some(offset))
+}
+
+/// offsetからtotal_bytesまでの範囲の末尾を表示する
 fn print_bytes<T: Read + Seek>(
     mut file: T,
     num_bytes: &TakeValue,
+    offset: u64,
     total_bytes: i64,
 ) -> MyResult<()> {
-    if let Some(start) = get_start_index(num_bytes, total_bytes) {
-        file.seek(SeekFrom::Start(start))?;
+    let len = total_bytes - offset as i64;
+    if let Some(start) = get_start_index(num_bytes, len) {
+        file.seek(SeekFrom::Start(offset + start))?;
         let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        file.take(len as u64 - start).read_to_end(&mut buffer)?;
         if !buffer.is_empty() {
             print!("{}", String::from_utf8_lossy(&buffer));
         }
@@ -211,7 +551,12 @@ fn get_start_index(take_val: &TakeValue, total: i64) -> Option<u64> {
 
 #[cfg(test)]
 mod tests {
-    use super::{count_lines_bytes, get_start_index, parse_num, TakeValue::*};
+    use super::{
+        count_lines_bytes, get_start_index, line_offset, open, parse_bytes, parse_interval,
+        parse_num, print_lines, TakeValue::*,
+    };
+    use std::io::Cursor;
+    use std::time::Duration;
 
     #[test]
     fn test_parse_num() {
@@ -268,15 +613,101 @@ mod tests {
         assert_eq!(res.unwrap_err().to_string(), "foo");
     }
 
+    #[test]
+    fn test_parse_bytes() {
+        // 単位がなければparse_numと同じ
+        assert_eq!(parse_bytes("3").unwrap(), TakeNum(-3));
+        assert_eq!(parse_bytes("+3").unwrap(), TakeNum(3));
+        assert_eq!(parse_bytes("+0").unwrap(), PlusZero);
+
+        // 単位は大文字でも小文字でも1024倍ずつ
+        assert_eq!(parse_bytes("1K").unwrap(), TakeNum(-1024));
+        assert_eq!(parse_bytes("+1k").unwrap(), TakeNum(1024));
+        assert_eq!(parse_bytes("-2M").unwrap(), TakeNum(-2 * 1024 * 1024));
+        assert_eq!(parse_bytes("1g").unwrap(), TakeNum(-1024 * 1024 * 1024));
+        assert_eq!(parse_bytes("+0K").unwrap(), PlusZero);
+
+        // 不正な単位やオーバーフローはエラー
+        let res = parse_bytes("1X");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "1X");
+        assert!(parse_bytes("K").is_err());
+        assert!(parse_bytes(&format!("{}G", i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        // 小数の秒数も受け付ける
+        assert_eq!(parse_interval("1").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_interval("0.25").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_interval("0").unwrap(), Duration::ZERO);
+
+        // 負の値や数値でないものはエラー
+        let res = parse_interval("-1");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "invalid number of seconds: '-1'"
+        );
+        assert!(parse_interval("foo").is_err());
+        assert!(parse_interval("inf").is_err());
+    }
+
     #[test]
     fn test_count_lines_bytes() {
-        let res = count_lines_bytes("tests/inputs/one.txt");
+        let res = count_lines_bytes(open("tests/inputs/one.txt").unwrap(), b'\n');
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), (1, 24));
 
-        let res = count_lines_bytes("tests/inputs/ten.txt");
+        let res = count_lines_bytes(open("tests/inputs/ten.txt").unwrap(), b'\n');
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), (10, 49));
+
+        // NUL区切りのレコードを数える
+        let res = count_lines_bytes(open("tests/inputs/five.nul.txt").unwrap(), b'\0');
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), (5, 24));
+
+        // 最後の行に改行がなくても実際のバイト数を数える
+        let res = count_lines_bytes(open("tests/inputs/no_newline.txt").unwrap(), b'\n');
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), (3, 13));
+    }
+
+    #[test]
+    fn test_line_offset() {
+        // 最後の3行はoneからnineまでの9行分のバイトを飛ばした位置から始まる
+        let file = open("tests/inputs/twelve.txt").unwrap();
+        let res = line_offset(file, &TakeNum(-3), 12, b'\n');
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Some(45));
+
+        // +1は先頭から
+        let file = open("tests/inputs/twelve.txt").unwrap();
+        assert_eq!(line_offset(file, &TakeNum(1), 12, b'\n').unwrap(), Some(0));
+
+        // 表示する行がなければNone
+        let file = open("tests/inputs/twelve.txt").unwrap();
+        assert_eq!(line_offset(file, &TakeNum(0), 12, b'\n').unwrap(), None);
+    }
+
+    #[test]
+    fn test_print_lines() {
+        let text = "1\n2\n3\n4\n5";
+        let tail = |num_lines| {
+            let mut out = vec![];
+            let bytes = print_lines(Cursor::new(text), &num_lines, b'\n', &mut out).unwrap();
+            // 表示する行数によらず最後まで読む
+            assert_eq!(bytes, 9);
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(tail(TakeNum(-2)), "4\n5");
+        assert_eq!(tail(TakeNum(-20)), text);
+        assert_eq!(tail(TakeNum(0)), "");
+        assert_eq!(tail(TakeNum(2)), "2\n3\n4\n5");
+        assert_eq!(tail(TakeNum(20)), "");
+        assert_eq!(tail(PlusZero), text);
     }
 
     #[test]
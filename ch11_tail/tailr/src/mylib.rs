@@ -1,164 +0,0 @@
-use crate::TakeValue::*;
-use clap::{App, Arg};
-use num::traits::ops::bytes;
-use std::error::Error;
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
-#[derive(Debug, PartialEq)]
-enum TakeValue {
-    PlusZero,
-    TakeNum(i64),
-}
-
-#[derive(Debug)]
-pub struct Config {
-    files: Vec<String>,
-    lines: TakeValue,
-    bytes: Option<TakeValue>,
-    quiet: bool,
-}
-
-pub fn get_args() -> MyResult<Config> {
-    let matches = App::new("tailr")
-        .version("0.1.0")
-        .author("Arinobu Fukuhara <afukuhara@gmail.com>")
-        .about("Rust tail")
-        .arg(
-            Arg::with_name("files")
-                .value_name("FILE")
-                .help("Input file(s)")
-                .multiple(true)
-                .required(true),
-        )
-        .arg(
-            Arg::with_name("lines")
-                .short("n")
-                .long("lines")
-                .value_name("LINES")
-                .help("Number of lines")
-                .required(false)
-                .takes_value(true)
-                .default_value("10"),
-        )
-        .arg(
-            Arg::with_name("bytes")
-                .short("c")
-                .long("bytes")
-                .value_name("BYTES")
-                .takes_value(true)
-                .required(false)
-                .conflicts_with("lines")
-                .help("Number of bytes"),
-        )
-        .arg(
-            Arg::with_name("quiet")
-                .short("q")
-                .long("quiet")
-                .help("Suppress headers"),
-        )
-        .get_matches();
-
-    let lines = matches
-        .value_of("lines")
-        .map(parse_num)
-        .transpose()
-        .map_err(|e| format!("illegal line count -- {}", e))?;
-
-    let bytes = matches
-        .value_of("bytes")
-        .map(parse_num)
-        .transpose()
-        .map_err(|e| format!("illegal byte count -- {}", e))?;
-
-    Ok(Config {
-        files: matches.values_of_lossy("files").unwrap(),
-        lines: lines.unwrap(),
-        bytes: bytes,
-        quiet: matches.is_present("quiet"),
-    })
-}
-
-pub fn run(config: Config) -> MyResult<()> {
-    println!("{:#?}", config);
-    Ok(())
-}
-
-fn parse_num(val: &str) -> MyResult<TakeValue> {
-    match val.parse::<i64>() {
-        Ok(n) => {
-            if val.starts_with('-') {
-                Ok(TakeValue::TakeNum(n))
-            } else if val.starts_with('+') {
-                if n == 0 {
-                    Ok(TakeValue::PlusZero)
-                } else {
-                    Ok(TakeValue::TakeNum(n))
-                }
-            } else {
-                Ok(TakeValue::TakeNum(-n))
-            }
-        }
-        Err(_) => Err(From::from(val)),
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{parse_num, TakeValue::*};
-
-    #[test]
-    fn test_parse_num() {
-        // すべての整数は負の数として解釈される必要がある
-        let res = parse_num("3");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(-3));
-
-        // 先頭に「+」が付いている場合は正の数として解釈される必要がある
-        let res = parse_num("+3");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(3));
-
-        // 明示的に「-」が付いている場合は負の数として解釈される必要がある
-        let res = parse_num("-3");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(-3));
-
-        // ゼロはゼロのまま
-        let res = parse_num("0");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(0));
-
-        // プラスゼロは特別扱い
-        let res = parse_num("+0");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PlusZero);
-
-        // 境界値のテスト
-        let res = parse_num(&i64::MAX.to_string());
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(i64::MIN + 1));
-
-        let res = parse_num(&(i64::MIN + 1).to_string());
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(i64::MIN + 1));
-
-        let res = parse_num(&format!("+{}", i64::MAX));
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(i64::MAX));
-
-        let res = parse_num(&i64::MIN.to_string());
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), TakeNum(i64::MIN));
-
-        // 浮動小数点数は無効
-        let res = parse_num("3.14");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "3.14");
-
-        // 整数でない文字列は無効
-        let res = parse_num("foo");
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err().to_string(), "foo");
-    }
-}
@@ -1,10 +1,15 @@
 use anyhow::Result;
-use assert_cmd::Command;
+use assert_cmd::{cargo::cargo_bin, Command};
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{Read, Write};
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
 
 const PRG: &str = "tailr";
 const EMPTY: &str = "tests/inputs/empty.txt";
@@ -98,6 +103,38 @@ fn skips_bad_file() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn bad_file_first_no_blank_line() -> Result<()> {
+    // 最初のファイルが開けなくても、最初のヘッダーの前に空行を入れない
+    let bad = gen_bad_file();
+    let output = Command::cargo_bin(PRG)?
+        .args([&bad, ONE, TWO])
+        .output()
+        .expect("fail");
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(
+        stdout.starts_with(&format!("==> {ONE} <==\n")),
+        "{stdout:?}"
+    );
+    assert!(
+        stdout.contains(&format!("\n\n==> {TWO} <==\n")),
+        "{stdout:?}"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_and_lines_then_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([TWELVE, "-c", "3", "--lines-then-bytes", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
     // Extra work here due to lossy UTF
@@ -113,6 +150,24 @@ fn run(args: &[&str], expected_file: &str) -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn twelve_lines_then_bytes() -> Result<()> {
+    // tail -n 3 | tail -c 8 と同じ結果になる
+    run(
+        &[TWELVE, "-n", "3", "--lines-then-bytes", "8"],
+        "tests/expected/twelve.txt.n3.c8.out",
+    )?;
+    run(
+        &[TWELVE, "-n", "+2", "--lines-then-bytes", "+3"],
+        "tests/expected/twelve.txt.n+2.c+3.out",
+    )?;
+    run(
+        &[TWELVE, "-n", "0", "--lines-then-bytes", "8"],
+        "tests/expected/twelve.txt.n0.c8.out",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn empty() -> Result<()> {
@@ -827,3 +882,359 @@ fn multiple_files_c_plus_3() -> Result<()> {
         "tests/expected/all.c+3.out",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn zero_terminated_n2() -> Result<()> {
+    run(
+        &["-z", "-n", "2", "tests/inputs/five.nul.txt"],
+        "tests/expected/five.nul.txt.z.n2.out",
+    )
+}
+
+#[test]
+fn zero_terminated_n_plus_4() -> Result<()> {
+    run(
+        &["--zero-terminated", "-n", "+4", "tests/inputs/five.nul.txt"],
+        "tests/expected/five.nul.txt.z.n2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pipe_lines() -> Result<()> {
+    // パイプはシークできないが、-nの場合は1回読むだけで末尾の行を表示できる
+    let input = fs::read_to_string(TWELVE)?;
+    for (args, expected_file) in [
+        (["-n", "3"], "tests/expected/twelve.txt.n3.out"),
+        (["-n", "+2"], "tests/expected/twelve.txt.n+2.out"),
+    ] {
+        let expected = fs::read_to_string(expected_file)?;
+        for file in ["-", "/dev/stdin"] {
+            Command::cargo_bin(PRG)?
+                .args(args)
+                .arg(file)
+                .write_stdin(input.as_str())
+                .assert()
+                .success()
+                .stdout(expected.clone());
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn huge_n() -> Result<()> {
+    // ファイルの行数より大きい-nではファイル全体を表示する
+    let expected = fs::read_to_string(TWELVE)?;
+    for num in ["9223372036854775807", "1000000000000"] {
+        Command::cargo_bin(PRG)?
+            .args(["-n", num, TWELVE])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_and_file_headers() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/stdin.one.out")?;
+    let output = Command::cargo_bin(PRG)?
+        .args(["-", ONE])
+        .write_stdin(fs::read_to_string(TWO)?)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_c_plus_1k() -> Result<()> {
+    // 1024バイト目から表示する
+    run(
+        &["-c", "+1K", "tests/inputs/numbers.txt"],
+        "tests/expected/numbers.txt.c+1K.out",
+    )
+}
+
+#[test]
+fn numbers_c_minus_1k() -> Result<()> {
+    run(
+        &["-c=-1K", "tests/inputs/numbers.txt"],
+        "tests/expected/numbers.txt.c1K.out",
+    )
+}
+
+#[test]
+fn numbers_c_1k() -> Result<()> {
+    run(
+        &["-c", "1k", "tests/inputs/numbers.txt"],
+        "tests/expected/numbers.txt.c1K.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_sleep_interval() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "--sleep-interval=-1", ONE])
+        .assert()
+        .failure()
+        .stderr("invalid number of seconds: '-1'\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+// 子プロセスの標準出力を別スレッドで読み、届いた順に受け取る
+fn read_stdout(child: &mut Child) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    let mut stdout = child.stdout.take().unwrap();
+    thread::spawn(move || {
+        let mut buffer = [0; 64];
+        while let Ok(n) = stdout.read(&mut buffer) {
+            if n == 0 || tx.send(buffer[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+// 出力の末尾がexpectedになるまで待つ
+fn wait_for(rx: &mpsc::Receiver<Vec<u8>>, output: &mut Vec<u8>, expected: &str) -> Result<()> {
+    while !String::from_utf8_lossy(output).ends_with(expected) {
+        output.extend(rx.recv_timeout(Duration::from_secs(10))?);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// -fで追記したデータが表示されるまでの時間を測る
+fn follow_latency(interval: &str) -> Result<Duration> {
+    let mut file = NamedTempFile::new()?;
+    writeln!(file, "first")?;
+
+    let mut child = std::process::Command::new(cargo_bin(PRG))
+        .args(["-f", "-s", interval])
+        .arg(file.path())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let rx = read_stdout(&mut child);
+    let mut output = Vec::new();
+    let mut wait_for = |expected: &str| wait_for(&rx, &mut output, expected);
+
+    wait_for("first\n")?;
+    let start = Instant::now();
+    writeln!(file, "second")?;
+    file.flush()?;
+    wait_for("first\nsecond\n")?;
+    let elapsed = start.elapsed();
+
+    child.kill()?;
+    child.wait()?;
+    Ok(elapsed)
+}
+
+#[test]
+fn follow_sleep_interval() -> Result<()> {
+    // 間隔が短いほど早く追記を検出する
+    let short = follow_latency("0.1")?;
+    let long = follow_latency("1.5")?;
+    assert!(short < long, "short: {:?}, long: {:?}", short, long);
+    assert!(long >= Duration::from_millis(500), "long: {:?}", long);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn follow_name_rotation() -> Result<()> {
+    // -Fではローテーション後の新しいファイルを先頭から表示し、
+    // 古いファイルへの追記は表示しない
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("app.log");
+    fs::write(&log, "old 1\nold 2\n")?;
+
+    let mut child = std::process::Command::new(cargo_bin(PRG))
+        .args(["-F", "-s", "0.1"])
+        .arg(&log)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let rx = read_stdout(&mut child);
+    let mut output = Vec::new();
+
+    wait_for(&rx, &mut output, "old 1\nold 2\n")?;
+    let rotated = dir.path().join("app.log.1");
+    fs::rename(&log, &rotated)?;
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&rotated)?
+        .write_all(b"stale\n")?;
+    fs::write(&log, "new 1\n")?;
+    wait_for(&rx, &mut output, "new 1\n")?;
+
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&log)?
+        .write_all(b"new 2\n")?;
+    wait_for(&rx, &mut output, "new 2\n")?;
+
+    child.kill()?;
+    child.wait()?;
+    assert_eq!(String::from_utf8(output)?, "old 1\nold 2\nnew 1\nnew 2\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_missing() -> Result<()> {
+    // -Fではファイルが一時的になくなっても終わらずに、1回だけ報告して再び作成されるのを待つ
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("app.log");
+    fs::write(&log, "old\n")?;
+
+    let mut child = std::process::Command::new(cargo_bin(PRG))
+        .args(["-F", "-s", "0.1"])
+        .arg(&log)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let rx = read_stdout(&mut child);
+    let mut output = Vec::new();
+
+    wait_for(&rx, &mut output, "old\n")?;
+    fs::remove_file(&log)?;
+    thread::sleep(Duration::from_millis(500));
+    fs::write(&log, "new\n")?;
+    wait_for(&rx, &mut output, "new\n")?;
+
+    child.kill()?;
+    child.wait()?;
+    let mut stderr = String::new();
+    child.stderr.take().unwrap().read_to_string(&mut stderr)?;
+    assert_eq!(stderr.matches("has become inaccessible").count(), 1);
+    assert!(stderr.contains("file appeared; following new file"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_split_multibyte() -> Result<()> {
+    // 2回に分けて書き込まれた複数バイトの文字もそのまま表示する
+    let mut file = NamedTempFile::new()?;
+    writeln!(file, "first")?;
+
+    let mut child = std::process::Command::new(cargo_bin(PRG))
+        .args(["-f", "-s", "0.1"])
+        .arg(file.path())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let rx = read_stdout(&mut child);
+    let mut output = Vec::new();
+
+    wait_for(&rx, &mut output, "first\n")?;
+    let bytes = "é".as_bytes();
+    file.write_all(&bytes[..1])?;
+    file.flush()?;
+    thread::sleep(Duration::from_millis(500));
+    file.write_all(&bytes[1..])?;
+    file.write_all(b"\n")?;
+    file.flush()?;
+    while output.len() < "first\né\n".len() {
+        output.extend(rx.recv_timeout(Duration::from_secs(10))?);
+    }
+
+    child.kill()?;
+    child.wait()?;
+    assert_eq!(String::from_utf8(output)?, "first\né\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_nothing_to_follow() -> Result<()> {
+    // 開けるファイルがなければ待たずにエラーで終わる
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-f", "-s", "0.1", &bad])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no files remaining"));
+
+    // 標準入力のパイプは追跡せずに終わる
+    Command::cargo_bin(PRG)?
+        .args(["-f", "-s", "0.1", "-"])
+        .write_stdin("hi\n")
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout("hi\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_multiple_files() -> Result<()> {
+    // 追記されたファイルが前回と変わったときだけヘッダーを表示する
+    let dir = tempfile::tempdir()?;
+    let a = dir.path().join("a.log");
+    let b = dir.path().join("b.log");
+    fs::write(&a, "a 1\n")?;
+    fs::write(&b, "b 1\n")?;
+
+    let mut child = std::process::Command::new(cargo_bin(PRG))
+        .args(["-f", "-s", "0.1"])
+        .args([&a, &b])
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let rx = read_stdout(&mut child);
+    let mut output = Vec::new();
+    let append = |path: &std::path::Path, text: &str| -> Result<()> {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(path)?
+            .write_all(text.as_bytes())?;
+        Ok(())
+    };
+
+    let (a_name, b_name) = (a.display(), b.display());
+    let mut expected = format!("==> {a_name} <==\na 1\n\n==> {b_name} <==\nb 1\n");
+    wait_for(&rx, &mut output, &expected)?;
+    for (path, name, text, header) in [
+        (&a, &a_name, "a 2\n", true),
+        (&a, &a_name, "a 3\n", false),
+        (&b, &b_name, "b 2\n", true),
+        (&a, &a_name, "a 4\n", true),
+    ] {
+        append(path, text)?;
+        if header {
+            expected += &format!("\n==> {name} <==\n");
+        }
+        expected += text;
+        wait_for(&rx, &mut output, &expected)?;
+    }
+
+    child.kill()?;
+    child.wait()?;
+    assert_eq!(String::from_utf8(output)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn one_empty_two_n1_headers() -> Result<()> {
+    // GNU tailと同じく、前のファイルの出力が空でもヘッダーの前に空行を入れる
+    run(
+        &["-n", "1", ONE, EMPTY, TWO],
+        "tests/expected/one_empty_two.n1.out",
+    )
+}
@@ -1,6 +1,6 @@
 use clap::{App, Arg};
 use rand::prelude::SliceRandom;
-use rand::{rngs::StdRng, SeedableRng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use regex::{Regex, RegexBuilder};
 use std::vec;
 use std::{
@@ -8,6 +8,7 @@ use std::{
     ffi::OsStr,
     fs::{self, File},
     io::{BufRead, BufReader},
+    iter,
     path::PathBuf,
 };
 use walkdir::WalkDir;
@@ -19,11 +20,20 @@ pub struct Config {
     sources: Vec<String>,
     pattern: Option<Regex>,
     seed: Option<u64>,
+    long_only: bool,
+    short_only: bool,
+    length: usize,
+    equal: bool,
+    list_files: bool,
+    show_source: bool,
 }
 
 #[derive(Debug)]
 pub struct Fortune {
     source: String,
+    // -eでファイルごとに選ぶためのパス。
+    // sourceはファイル名だけなので、別のディレクトリにある同じ名前のファイルと区別できない
+    path: PathBuf,
     text: String,
 }
 
@@ -60,6 +70,44 @@ pub fn get_args() -> MyResult<Config> {
                 .value_name("SEED")
                 .help("Random seed"),
         )
+        .arg(
+            Arg::with_name("list_files")
+                .long("files")
+                .help("List the source files and their number of fortunes"),
+        )
+        .arg(
+            Arg::with_name("show_source")
+                .short("c")
+                .long("show-source")
+                .visible_alias("show-cookie-file")
+                .help("Show the file the fortune came from"),
+        )
+        .arg(
+            Arg::with_name("equal")
+                .short("e")
+                .long("equal")
+                .help("Consider all fortune files to be of equal size"),
+        )
+        .arg(
+            Arg::with_name("long_only")
+                .short("l")
+                .long("long-only")
+                .help("Long fortunes only")
+                .conflicts_with("short_only"),
+        )
+        .arg(
+            Arg::with_name("short_only")
+                .long("short-only")
+                .help("Short fortunes only"),
+        )
+        .arg(
+            Arg::with_name("length")
+                .short("n")
+                .long("length")
+                .value_name("LENGTH")
+                .help("Longest fortune length considered short")
+                .default_value("160"),
+        )
         .get_matches();
 
     let pattern = matches
@@ -76,6 +124,16 @@ pub fn get_args() -> MyResult<Config> {
         sources: matches.values_of_lossy("sources").unwrap(),
         seed: matches.value_of("seed").map(parse_u64).transpose()?,
         pattern,
+        equal: matches.is_present("equal"),
+        list_files: matches.is_present("list_files"),
+        show_source: matches.is_present("show_source"),
+        long_only: matches.is_present("long_only"),
+        short_only: matches.is_present("short_only"),
+        length: matches
+            .value_of("length")
+            .map(parse_length)
+            .transpose()?
+            .unwrap(),
     })
 }
 
@@ -84,6 +142,14 @@ fn parse_u64(val: &str) -> MyResult<u64> {
         .map_err(|_| format!("\"{}\" not a valid integer", val).into())
 }
 
+// 短い格言の最大の長さなので0は受け付けない
+fn parse_length(val: &str) -> MyResult<usize> {
+    match parse_u64(val) {
+        Ok(n) if n > 0 => Ok(n as usize),
+        _ => Err(format!("\"{}\" not a valid length", val).into()),
+    }
+}
+
 fn find_files(paths: &[String]) -> MyResult<Vec<PathBuf>> {
     let dat = OsStr::new("dat");
     let mut files = vec![];
@@ -116,17 +182,30 @@ fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
         let file = File::open(path)
             .map_err(|e| format!("{}: {}", path.to_string_lossy().into_owned(), e))?;
 
-        for line in BufReader::new(file).lines().filter_map(Result::ok) {
+        // UTF-8として正しくない行も読み飛ばさずに、置き換え文字にして読み込む
+        let lines = BufReader::new(file).split(b'\n').map(|line| {
+            line.map(|bytes| {
+                let line = String::from_utf8_lossy(&bytes);
+                line.strip_suffix('\r').unwrap_or(&line).to_string()
+            })
+        });
+
+        // 「%」だけの行を区切りとし、本文中の「%」では区切らない。
+        // 最後の格言の後ろに区切りがなくてもファイルの終わりで区切る
+        for line in lines.chain(iter::once(Ok("%".to_string()))) {
+            let line =
+                line.map_err(|e| format!("{}: {}", path.to_string_lossy().into_owned(), e))?;
             if line == "%" {
                 if !buffer.is_empty() {
                     fortunes.push(Fortune {
                         source: basename.clone(),
+                        path: path.clone(),
                         text: buffer.join("\n"),
                     });
                     buffer.clear();
                 }
             } else {
-                buffer.push(line.to_string());
+                buffer.push(line);
             }
         }
     }
@@ -134,36 +213,102 @@ fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
     Ok(fortunes)
 }
 
-fn pick_fortune(fortunes: &[Fortune], seed: Option<u64>) -> Option<String> {
+// ファイルごとの格言の数をパスの順に返す
+fn count_fortunes(paths: &[PathBuf]) -> MyResult<Vec<(PathBuf, usize)>> {
+    paths
+        .iter()
+        .map(|path| {
+            Ok((
+                path.clone(),
+                read_fortunes(std::slice::from_ref(path))?.len(),
+            ))
+        })
+        .collect()
+}
+
+// --lengthの文字数より長いものを長い格言、それ以下を短い格言とする
+fn filter_by_length(
+    fortunes: Vec<Fortune>,
+    long_only: bool,
+    short_only: bool,
+    length: usize,
+) -> Vec<Fortune> {
+    fortunes
+        .into_iter()
+        .filter(|f| {
+            let len = f.text.len();
+            (!long_only || len > length) && (!short_only || len <= length)
+        })
+        .collect()
+}
+
+fn pick_fortune(fortunes: &[Fortune], seed: Option<u64>, equal: bool) -> Option<&Fortune> {
     if let Some(val) = seed {
         let mut rng = StdRng::seed_from_u64(val);
-        fortunes.choose(&mut rng).map(|f| f.text.to_string())
+        choose_fortune(fortunes, &mut rng, equal)
     } else {
         let mut rng = rand::thread_rng();
-        fortunes.choose(&mut rng).map(|f| f.text.to_string())
+        choose_fortune(fortunes, &mut rng, equal)
     }
 }
 
+// 通常はすべての格言から選ぶので、格言の多いファイルほど選ばれやすい。
+// equalの場合は先にファイルを選び、その中から格言を選ぶ
+fn choose_fortune<'a, R: Rng>(
+    fortunes: &'a [Fortune],
+    rng: &mut R,
+    equal: bool,
+) -> Option<&'a Fortune> {
+    if !equal {
+        return fortunes.choose(rng);
+    }
+
+    // 同じファイルの格言は連続して並んでいる
+    let mut paths: Vec<&PathBuf> = fortunes.iter().map(|f| &f.path).collect();
+    paths.dedup();
+    let path = paths.choose(rng)?;
+    let group: Vec<&Fortune> = fortunes.iter().filter(|f| &f.path == *path).collect();
+    group.choose(rng).copied()
+}
+
 pub fn run(config: Config) -> MyResult<()> {
     let files = find_files(&config.sources)?;
-    let fortunes = read_fortunes(&files)?;
+    if config.list_files {
+        for (path, count) in count_fortunes(&files)? {
+            println!("{}\t{}", count, path.display());
+        }
+        return Ok(());
+    }
+
+    let fortunes = filter_by_length(
+        read_fortunes(&files)?,
+        config.long_only,
+        config.short_only,
+        config.length,
+    );
 
     if let Some(pattern) = config.pattern {
-        let mut prev_source = None;
+        let mut prev_path = None;
         for fortune in fortunes.iter().filter(|f| pattern.is_match(&f.text)) {
-            if prev_source.as_ref().map_or(true, |s| s != &fortune.source) {
+            // -cの場合はファイル名も格言と一緒に標準出力に表示する
+            if config.show_source {
+                println!("({})\n%", fortune.source);
+            } else if prev_path.is_none_or(|path| path != &fortune.path) {
                 eprintln!("({})\n%", fortune.source);
-                prev_source = Some(fortune.source.clone());
+                prev_path = Some(&fortune.path);
             }
             println!("{}\n%", fortune.text);
         }
     } else {
-        println!(
-            "{}",
-            pick_fortune(&fortunes, config.seed)
-                .or_else(|| Some("No fortunes found".to_string()))
-                .unwrap()
-        );
+        match pick_fortune(&fortunes, config.seed, config.equal) {
+            Some(fortune) => {
+                if config.show_source {
+                    println!("({})\n%", fortune.source);
+                }
+                println!("{}", fortune.text);
+            }
+            None => println!("No fortunes found"),
+        }
     }
 
     Ok(())
@@ -171,7 +316,10 @@ pub fn run(config: Config) -> MyResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::{find_files, parse_u64, pick_fortune, read_fortunes, Fortune};
+    use super::{
+        count_fortunes, filter_by_length, find_files, parse_length, parse_u64, pick_fortune,
+        read_fortunes, Fortune,
+    };
     use std::path::PathBuf;
 
     #[test]
@@ -189,6 +337,22 @@ mod tests {
         assert_eq!(res.unwrap(), 4);
     }
 
+    #[test]
+    fn test_parse_length() {
+        assert_eq!(parse_length("160").unwrap(), 160);
+        assert_eq!(parse_length("1").unwrap(), 1);
+
+        // 0や負の数、数値でないものはエラー
+        for bad in ["0", "-1", "a", ""] {
+            let res = parse_length(bad);
+            assert!(res.is_err());
+            assert_eq!(
+                res.unwrap_err().to_string(),
+                format!("\"{}\" not a valid length", bad)
+            );
+        }
+    }
+
     #[test]
     fn test_find_files() {
         // 存在するファイルを検索できることを確認する
@@ -198,7 +362,7 @@ mod tests {
         let files = res.unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(
-            files.get(0).unwrap().to_string_lossy(),
+            files.first().unwrap().to_string_lossy(),
             "./tests/inputs/jokes"
         );
 
@@ -213,7 +377,7 @@ mod tests {
         // ファイル数とファイルの順番を確認する
         let files = res.unwrap();
         assert_eq!(files.len(), 5);
-        let first = files.get(0).unwrap().display().to_string();
+        let first = files.first().unwrap().display().to_string();
         assert!(first.contains("ascii-art"));
         let last = files.last().unwrap().display().to_string();
         assert!(last.contains("quotes"));
@@ -236,6 +400,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_files_skips_dat() {
+        // fortuneのインデックスファイル（.dat）は本文として読まない
+        let res = find_files(&["./tests/dat".to_string()]);
+        assert!(res.is_ok());
+        let files = res.unwrap();
+        assert_eq!(files, [PathBuf::from("./tests/dat/jokes")]);
+
+        // インデックスファイルの中身がフォーチュンに混ざらない
+        let fortunes = read_fortunes(&files).unwrap();
+        assert_eq!(fortunes.len(), 2);
+    }
+
     #[test]
     fn test_read_fortunes() {
         // 入力ファイルが1つだけの場合
@@ -268,30 +445,168 @@ mod tests {
         assert_eq!(res.unwrap().len(), 11);
     }
 
+    #[test]
+    fn test_count_fortunes() {
+        let files = find_files(&["./tests/inputs".to_string()]).unwrap();
+        let res = count_fortunes(&files);
+        assert!(res.is_ok());
+
+        // パスの順に並び、合計はread_fortunesの件数と一致する
+        let counts = res.unwrap();
+        assert_eq!(counts.len(), files.len());
+        assert!(counts.windows(2).all(|w| w[0].0 < w[1].0));
+        let total: usize = counts.iter().map(|(_, n)| n).sum();
+        assert_eq!(total, read_fortunes(&files).unwrap().len());
+
+        let jokes = counts.iter().find(|(path, _)| path.ends_with("jokes"));
+        assert_eq!(jokes.map(|(_, n)| *n), Some(6));
+    }
+
+    #[test]
+    fn test_read_fortunes_percent() {
+        // 本文中の「%」では区切らず、末尾に区切りがない格言も読み込む
+        let res = read_fortunes(&[
+            PathBuf::from("./tests/records/percent"),
+            PathBuf::from("./tests/inputs/jokes"),
+        ]);
+        assert!(res.is_ok());
+
+        let fortunes = res.unwrap();
+        assert_eq!(fortunes.len(), 8);
+        assert_eq!(fortunes[0].text, "Sale: 50% off!\n  -- %the shop%");
+        assert_eq!(fortunes[1].text, "100% of the shots you don't take miss.");
+        assert_eq!(fortunes[1].source, "percent");
+        assert_eq!(fortunes[2].source, "jokes");
+        assert_eq!(fortunes[2].path, PathBuf::from("./tests/inputs/jokes"));
+    }
+
+    #[test]
+    fn test_read_fortunes_invalid_utf8() {
+        // UTF-8として正しくない行があっても、その後ろの格言まで読み込む
+        let res = read_fortunes(&[PathBuf::from("./tests/records/invalid_utf8")]);
+        assert!(res.is_ok());
+
+        let fortunes = res.unwrap();
+        assert_eq!(fortunes.len(), 3);
+        assert_eq!(fortunes[1].text, "bad \u{FFFD} line\nstill here");
+        assert_eq!(fortunes[2].text, "last");
+    }
+
     #[test]
     fn test_pick_fortune() {
         // Fortuneのスライスを作成
         let fortunes = &[
             Fortune {
                 source: "fortunes".to_string(),
+                path: PathBuf::from("fortunes"),
                 text: "You cannot achieve the impossible without \
                       attempting the absurd."
                     .to_string(),
             },
             Fortune {
                 source: "fortunes".to_string(),
+                path: PathBuf::from("fortunes"),
                 text: "Assumption is the mother of all screw-ups.".to_string(),
             },
             Fortune {
                 source: "fortunes".to_string(),
+                path: PathBuf::from("fortunes"),
                 text: "Neckties strangle clear thinking.".to_string(),
             },
         ];
 
         // シードを与えて引用句を1つ選択
         assert_eq!(
-            pick_fortune(fortunes, Some(1)).unwrap(),
+            pick_fortune(fortunes, Some(1), false).unwrap().text,
             "Neckties strangle clear thinking.".to_string()
         );
     }
+
+    #[test]
+    fn test_filter_by_length() {
+        // 長さが10、20、30文字の格言を用意する
+        let fortunes = || {
+            [10, 20, 30]
+                .iter()
+                .map(|&n| Fortune {
+                    source: "fortunes".to_string(),
+                    path: PathBuf::from("fortunes"),
+                    text: "x".repeat(n),
+                })
+                .collect::<Vec<_>>()
+        };
+        let lengths = |fortunes: Vec<Fortune>| -> Vec<usize> {
+            fortunes.iter().map(|f| f.text.len()).collect()
+        };
+
+        // 指定がなければすべて残す
+        assert_eq!(
+            lengths(filter_by_length(fortunes(), false, false, 20)),
+            vec![10, 20, 30]
+        );
+
+        // 境界の長さちょうどのものは短い格言に含める
+        assert_eq!(
+            lengths(filter_by_length(fortunes(), true, false, 20)),
+            vec![30]
+        );
+        assert_eq!(
+            lengths(filter_by_length(fortunes(), false, true, 20)),
+            vec![10, 20]
+        );
+
+        // 該当するものがなければ空になる
+        assert!(filter_by_length(fortunes(), true, false, 30).is_empty());
+    }
+
+    #[test]
+    fn test_pick_fortune_equal() {
+        // 1件だけのファイルと9件のファイルを用意する
+        let mut fortunes = vec![Fortune {
+            source: "small".to_string(),
+            path: PathBuf::from("small"),
+            text: "small".to_string(),
+        }];
+        fortunes.extend((0..9).map(|i| Fortune {
+            source: "large".to_string(),
+            path: PathBuf::from("large"),
+            text: format!("large {}", i),
+        }));
+
+        let count_small = |equal| {
+            (0..1000)
+                .filter(|&seed| pick_fortune(&fortunes, Some(seed), equal).unwrap().text == "small")
+                .count()
+        };
+
+        // 格言ごとに選ぶと1件だけのファイルは1割程度しか選ばれない
+        let per_fortune = count_small(false);
+        assert!(per_fortune < 200, "per fortune: {}", per_fortune);
+
+        // -eではファイルごとに同じ確率で選ばれる
+        let per_file = count_small(true);
+        assert!(per_file > 400 && per_file < 600, "per file: {}", per_file);
+    }
+
+    #[test]
+    fn test_pick_fortune_equal_same_name() {
+        // 別のディレクトリにある同じ名前のファイルは別のファイルとして数える
+        let fortunes: Vec<_> = ["a/jokes", "b/jokes", "c/quotes"]
+            .iter()
+            .map(|path| Fortune {
+                source: path.rsplit('/').next().unwrap().to_string(),
+                path: PathBuf::from(path),
+                text: path.to_string(),
+            })
+            .collect();
+
+        let count_quotes = (0..3000)
+            .filter(|&seed| pick_fortune(&fortunes, Some(seed), true).unwrap().text == "c/quotes")
+            .count();
+        assert!(
+            count_quotes > 800 && count_quotes < 1200,
+            "quotes: {}",
+            count_quotes
+        );
+    }
 }
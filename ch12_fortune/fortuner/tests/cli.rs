@@ -81,6 +81,62 @@ fn dies_bad_seed() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bad_length() -> Result<()> {
+    for bad in ["0", "-5", "ten"] {
+        Command::cargo_bin(PRG)?
+            .args([QUOTES, &format!("--length={bad}")])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "\"{bad}\" not a valid length"
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// -mですべての格言を表示し、表示した件数を返す
+fn count_matching(args: &[&str]) -> Result<usize> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["-m", "."])
+        .args(args)
+        .output()?;
+    assert!(output.status.success());
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter(|line| *line == "%")
+        .count())
+}
+
+// --------------------------------------------------
+#[test]
+fn length_partitions() -> Result<()> {
+    // quotesには47、53、61、168、249バイトの格言がある
+    assert_eq!(count_matching(&[QUOTES, "--short-only", "-n", "50"])?, 1);
+    assert_eq!(count_matching(&[QUOTES, "--long-only", "-n", "50"])?, 4);
+    assert_eq!(count_matching(&[QUOTES, "--short-only", "-n", "200"])?, 4);
+    assert_eq!(count_matching(&[QUOTES, "--long-only", "-n", "200"])?, 1);
+
+    // 既定では160バイトを境にする
+    assert_eq!(count_matching(&[QUOTES, "--short-only"])?, 3);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn random_without_pattern() -> Result<()> {
+    // -mを指定しなければランダムに1つ選んで表示する
+    Command::cargo_bin(PRG)?
+        .arg(FORTUNE_DIR)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not())
+        .stderr(predicate::str::contains("panicked").not());
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn no_fortunes_found() -> Result<()> {
@@ -115,6 +171,38 @@ fn dir_seed_10() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn dir_seed_10_show_source() -> Result<()> {
+    // 選んだ格言の前にファイル名を表示する
+    run(
+        &[FORTUNE_DIR, "-s", "10", "--show-source"],
+        "(jokes)\n%\n\
+        Q: Why did the fungus and the alga marry?\n\
+        A: Because they took a lichen to each other!\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_show_source() -> Result<()> {
+    // -mの場合は格言ごとにファイル名を標準出力に表示する
+    run(
+        &["-c", "--pattern", "vu all", FORTUNE_DIR],
+        "(quotes)\n%\nIt's like deja vu all over again.\n-- Yogi Berra\n%\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn list_files() -> Result<()> {
+    // 格言を表示する代わりにファイルごとの件数を表示する
+    run(
+        &["--files", JOKES, QUOTES],
+        "6\t./tests/inputs/jokes\n5\t./tests/inputs/quotes\n",
+    )
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected: &'static str) -> Result<()> {
     let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
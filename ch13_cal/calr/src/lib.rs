@@ -1,11 +1,13 @@
 use ansi_term::Style;
 use chrono::{Datelike, Local, NaiveDate};
 use clap::{App, Arg};
-use itertools::izip;
 use std::{error::Error, str::FromStr};
 
 const LINE_WIDTH: usize = 22;
 
+// 1年1月から9999年12月までの月の差が-A/-Bで指定できる最大の値
+const MAX_MONTHS: u32 = 9999 * 12 - 1;
+
 const MONTH_NAMES: [&str; 12] = [
     "January",
     "February",
@@ -26,6 +28,9 @@ pub struct Config {
     month: Option<u32>,
     year: i32,
     today: NaiveDate,
+    before: u32,
+    after: u32,
+    monday: bool,
 }
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
@@ -51,6 +56,38 @@ pub fn get_args() -> MyResult<Config> {
                 .conflicts_with_all(&["month", "year"])
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("three")
+                .short("3")
+                .help("Show previous, current and next month")
+                .conflicts_with("show_current_year")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("after")
+                .short("A")
+                .long("after")
+                .value_name("N")
+                .help("Show N months after the month")
+                .conflicts_with("show_current_year")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("before")
+                .short("B")
+                .long("before")
+                .value_name("N")
+                .help("Show N months before the month")
+                .conflicts_with("show_current_year")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("monday")
+                .short("M")
+                .long("monday")
+                .help("Weeks start on Monday")
+                .takes_value(false),
+        )
         .get_matches();
 
     let mut month = matches.value_of("month").map(parse_month).transpose()?;
@@ -65,47 +102,133 @@ pub fn get_args() -> MyResult<Config> {
         year = Some(today.year());
     }
 
+    // -3は-B1 -A1と同じ
+    let three = matches.is_present("three") as u32;
+    let before = matches.value_of("before").map(parse_months).transpose()?;
+    let after = matches.value_of("after").map(parse_months).transpose()?;
+
+    let year = year.unwrap_or_else(|| today.year());
+    let before = before.unwrap_or(three);
+    let after = after.unwrap_or(three);
+
+    // -A/-Bで表示する月も1年から9999年の範囲に収める。
+    // 範囲を作る前に最初と最後の月だけを確かめる
+    if let Some(month) = month {
+        let (first, last) = month_bounds(year, month, before, after)?;
+        for (y, _) in [first, last] {
+            if !(1..=9999).contains(&y) {
+                return Err(format!("year \"{}\" not in the range 1 through 9999", y).into());
+            }
+        }
+    }
+
     Ok(Config {
         month,
-        year: year.unwrap_or_else(|| today.year()),
+        year,
         today,
+        before,
+        after,
+        monday: matches.is_present("monday"),
     })
 }
 
 pub fn run(config: Config) -> MyResult<()> {
     match config.month {
-        Some(month) => {
-            let lines = format_month(config.year, month, true, config.today);
+        Some(month) if config.before == 0 && config.after == 0 => {
+            let lines = format_month(config.year, month, true, config.today, config.monday);
             println!("{}", lines.join("\n"));
         }
+        Some(month) => {
+            let months: Vec<_> = month_range(config.year, month, config.before, config.after)?
+                .into_iter()
+                .map(|(year, month)| format_month(year, month, true, config.today, config.monday))
+                .collect();
+            print_rows(&months);
+        }
         None => {
             println!("{:>32}", config.year);
             let months: Vec<_> = (1..=12)
-                .map(|month| format_month(config.year, month, false, config.today))
+                .map(|month| format_month(config.year, month, false, config.today, config.monday))
                 .collect();
-
-            for (i, chunk) in months.chunks(3).enumerate() {
-                if let [m1, m2, m3] = chunk {
-                    for lines in izip!(m1, m2, m3) {
-                        println!("{}{}{}", lines.0, lines.1, lines.2);
-                    }
-
-                    if i < 3 {
-                        println!();
-                    }
-                }
-            }
+            print_rows(&months);
         }
     }
 
     Ok(())
 }
 
+/// 指定した月の前後の月を（年, 月）の組で古い順に返す。年をまたぐ場合も数える
+fn month_range(year: i32, month: u32, before: u32, after: u32) -> MyResult<Vec<(i32, u32)>> {
+    let (first, last) = month_indexes(year, month, before, after)?;
+    Ok((first..=last).map(from_month_index).collect())
+}
+
+/// 指定した月の前後の範囲の最初と最後の月を（年, 月）の組で返す
+fn month_bounds(
+    year: i32,
+    month: u32,
+    before: u32,
+    after: u32,
+) -> MyResult<((i32, u32), (i32, u32))> {
+    let (first, last) = month_indexes(year, month, before, after)?;
+    Ok((from_month_index(first), from_month_index(last)))
+}
+
+/// 0年1月からの通しの月番号で範囲の最初と最後を返す。桁あふれする場合はエラー
+fn month_indexes(year: i32, month: u32, before: u32, after: u32) -> MyResult<(i32, i32)> {
+    let out_of_range = || "month range not in the range 1 through 9999".to_string();
+    let index = year
+        .checked_mul(12)
+        .and_then(|i| i.checked_add(month as i32 - 1))
+        .ok_or_else(out_of_range)?;
+    let first = i32::try_from(before)
+        .ok()
+        .and_then(|n| index.checked_sub(n))
+        .ok_or_else(out_of_range)?;
+    let last = i32::try_from(after)
+        .ok()
+        .and_then(|n| index.checked_add(n))
+        .ok_or_else(out_of_range)?;
+    Ok((first, last))
+}
+
+fn from_month_index(index: i32) -> (i32, u32) {
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+/// 月のカレンダーを3か月ずつ横に並べ、行の間を空行で区切って表示する
+fn print_rows(months: &[Vec<String>]) {
+    for (i, chunk) in months.chunks(3).enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for n in 0..chunk[0].len() {
+            let line: String = chunk.iter().map(|lines| lines[n].as_str()).collect();
+            println!("{}", line);
+        }
+    }
+}
+
 fn parse_int<T: FromStr>(val: &str) -> MyResult<T> {
     val.parse()
         .map_err(|_| format!("Invalid integer \"{}\"", val).into())
 }
 
+/// -A/-Bの月数。大きすぎる値で巨大な範囲を作らないように上限を設ける
+fn parse_months(val: &str) -> MyResult<u32> {
+    parse_int(val).and_then(|num| {
+        if num <= MAX_MONTHS {
+            Ok(num)
+        } else {
+            Err(format!(
+                "month count \"{}\" not in the range 0 through {}",
+                val, MAX_MONTHS
+            )
+            .into())
+        }
+    })
+}
+
 fn parse_year(year: &str) -> MyResult<i32> {
     parse_int(year).and_then(|num| {
         if (1..=9999).contains(&num) {
@@ -148,11 +271,22 @@ fn parse_month(month: &str) -> MyResult<u32> {
     }
 }
 
-fn format_month(year: i32, month: u32, print_year: bool, today: NaiveDate) -> Vec<String> {
+/// mondayがtrueの場合は月曜日から始まる週で表示する
+fn format_month(
+    year: i32,
+    month: u32,
+    print_year: bool,
+    today: NaiveDate,
+    monday: bool,
+) -> Vec<String> {
     let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
-    let mut days: Vec<String> = (1..first.weekday().number_from_sunday())
-        .map(|_| "  ".to_string())
-        .collect();
+    // 1日より前の曜日の数だけ空白で埋める
+    let offset = if monday {
+        first.weekday().num_days_from_monday()
+    } else {
+        first.weekday().num_days_from_sunday()
+    };
+    let mut days: Vec<String> = (0..offset).map(|_| "  ".to_string()).collect();
 
     let is_today = |day: u32| year == today.year() && month == today.month() && day == today.day();
 
@@ -177,7 +311,14 @@ fn format_month(year: i32, month: u32, print_year: bool, today: NaiveDate) -> Ve
         }
     ));
 
-    lines.push("Su Mo Tu We Th Fr Sa  ".to_string());
+    lines.push(
+        if monday {
+            "Mo Tu We Th Fr Sa Su  "
+        } else {
+            "Su Mo Tu We Th Fr Sa  "
+        }
+        .to_string(),
+    );
 
     for week in days.chunks(7) {
         lines.push(format!(
@@ -211,8 +352,11 @@ fn last_day_in_month(year: i32, month: u32) -> NaiveDate {
 
 #[cfg(test)]
 mod tests {
-    use super::{format_month, last_day_in_month, parse_int, parse_month, parse_year};
-    use chrono::NaiveDate;
+    use super::{
+        format_month, last_day_in_month, month_bounds, month_range, parse_int, parse_month,
+        parse_months, parse_year, MAX_MONTHS,
+    };
+    use chrono::{Datelike, NaiveDate};
 
     #[test]
     fn test_parse_int() {
@@ -306,7 +450,7 @@ mod tests {
             "23 24 25 26 27 28 29  ",
             "                      ",
         ];
-        assert_eq!(format_month(2020, 2, true, today), leap_february);
+        assert_eq!(format_month(2020, 2, true, today, false), leap_february);
 
         let may = vec![
             "        May           ",
@@ -318,7 +462,7 @@ mod tests {
             "24 25 26 27 28 29 30  ",
             "31                    ",
         ];
-        assert_eq!(format_month(2020, 5, false, today), may);
+        assert_eq!(format_month(2020, 5, false, today, false), may);
 
         let april_hl = vec![
             "     April 2021       ",
@@ -331,7 +475,84 @@ mod tests {
             "                      ",
         ];
         let today = NaiveDate::from_ymd_opt(2021, 4, 7).unwrap();
-        assert_eq!(format_month(2021, 4, true, today), april_hl);
+        assert_eq!(format_month(2021, 4, true, today, false), april_hl);
+    }
+
+    #[test]
+    fn test_format_month_monday() {
+        // 月曜日始まりの場合は土曜日の1日が6列目になる
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let leap_february = vec![
+            "   February 2020      ",
+            "Mo Tu We Th Fr Sa Su  ",
+            "                1  2  ",
+            " 3  4  5  6  7  8  9  ",
+            "10 11 12 13 14 15 16  ",
+            "17 18 19 20 21 22 23  ",
+            "24 25 26 27 28 29     ",
+            "                      ",
+        ];
+        assert_eq!(format_month(2020, 2, true, today, true), leap_february);
+
+        // 日曜日の1日は最後の列になり、6週目まで使う
+        let march = vec![
+            "       March          ",
+            "Mo Tu We Th Fr Sa Su  ",
+            "                   1  ",
+            " 2  3  4  5  6  7  8  ",
+            " 9 10 11 12 13 14 15  ",
+            "16 17 18 19 20 21 22  ",
+            "23 24 25 26 27 28 29  ",
+            "30 31                 ",
+        ];
+        assert_eq!(format_month(2020, 3, false, today, true), march);
+    }
+
+    #[test]
+    fn test_format_month_year_boundaries() {
+        // 範囲の最初と最後の月でも表示できる
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let january = format_month(1, 1, true, today, false);
+        assert_eq!(january[0], "     January 1        ");
+        assert_eq!(january[2], "    1  2  3  4  5  6  ");
+
+        let december = format_month(9999, 12, true, today, false);
+        assert_eq!(december[0], "   December 9999      ");
+        assert_eq!(december[6], "26 27 28 29 30 31     ");
+    }
+
+    #[test]
+    fn test_format_month_edge_layouts() {
+        // 2025年8月（金曜日始まりで6週）、2021年5月（土曜日始まりの31日）、
+        // 2015年2月（日曜日始まりで4週ちょうど）、2020年3月（日曜日始まり）
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        for (year, month, weeks) in [(2025, 8, 6), (2021, 5, 6), (2015, 2, 4), (2020, 3, 5)] {
+            let lines = format_month(year, month, true, today, false);
+            assert_eq!(lines.len(), 8);
+
+            // 週の行に並んだ日付を（行, 列）ごとに取り出す
+            let mut cells = vec![];
+            for (row, line) in lines[2..].iter().enumerate() {
+                for col in 0..7 {
+                    let cell = line[col * 3..col * 3 + 2].trim();
+                    if !cell.is_empty() {
+                        cells.push((cell.parse::<u32>().unwrap(), row, col));
+                    }
+                }
+            }
+
+            // すべての日付が1回ずつ、曜日に合った列に並ぶ
+            let last = last_day_in_month(year, month).day();
+            assert_eq!(cells.len() as u32, last);
+            for (i, &(day, row, col)) in cells.iter().enumerate() {
+                assert_eq!(day, i as u32 + 1);
+                let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+                assert_eq!(col as u32, date.weekday().num_days_from_sunday());
+                if day == last {
+                    assert_eq!(row + 1, weeks, "{}-{}", year, month);
+                }
+            }
+        }
     }
 
     #[test]
@@ -349,4 +570,58 @@ mod tests {
             NaiveDate::from_ymd_opt(2020, 4, 30).unwrap()
         );
     }
+
+    #[test]
+    fn test_month_range() {
+        assert_eq!(month_range(2020, 5, 0, 0).unwrap(), vec![(2020, 5)]);
+
+        // 年をまたぐ場合
+        assert_eq!(
+            month_range(2025, 12, 1, 2).unwrap(),
+            vec![(2025, 11), (2025, 12), (2026, 1), (2026, 2)]
+        );
+        assert_eq!(
+            month_range(2026, 1, 2, 0).unwrap(),
+            vec![(2025, 11), (2025, 12), (2026, 1)]
+        );
+
+        // 桁あふれする場合はパニックせずにエラーにする
+        assert!(month_range(2020, 1, 0, i32::MAX as u32).is_err());
+        assert!(month_range(2020, 1, 0, u32::MAX).is_err());
+        assert!(month_range(2020, 1, u32::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        assert_eq!(
+            month_bounds(2025, 12, 1, 2).unwrap(),
+            ((2025, 11), (2026, 2))
+        );
+
+        // 範囲を作らずに端の月だけを求める
+        assert_eq!(
+            month_bounds(2020, 1, 0, MAX_MONTHS).unwrap(),
+            ((2020, 1), (12018, 12))
+        );
+        assert_eq!(
+            month_bounds(2020, 1, MAX_MONTHS, 0).unwrap(),
+            ((-7979, 2), (2020, 1))
+        );
+        assert!(month_bounds(2020, 1, u32::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_months() {
+        assert_eq!(parse_months("0").unwrap(), 0);
+        assert_eq!(parse_months("119987").unwrap(), MAX_MONTHS);
+
+        let res = parse_months("119988");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            r#"month count "119988" not in the range 0 through 119987"#
+        );
+        assert!(parse_months("2147483647").is_err());
+        assert!(parse_months("-1").is_err());
+    }
 }
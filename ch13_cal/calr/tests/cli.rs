@@ -89,6 +89,107 @@ fn dies_invalid_month() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_month_as_positional() -> Result<()> {
+    // 位置引数は年だけで、月は-mで指定する
+    Command::cargo_bin(PRG)?
+        .args(["13", "2020"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Found argument '2020' which wasn't expected",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_range_out_of_years() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "1", "-B1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"year "0" not in the range 1 through 9999"#,
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["-m", "12", "9999", "-A1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"year "10000" not in the range 1 through 9999"#,
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_huge_after() -> Result<()> {
+    // 桁あふれや巨大な範囲を作る前にエラーにする
+    for val in ["2147483647", "30000000"] {
+        Command::cargo_bin(PRG)?
+            .args(["-m", "1", "-A", val, "2020"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                r#"month count "{val}" not in the range 0 through 119987"#
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_huge_before_after() -> Result<()> {
+    // 大きすぎる値は空の範囲にせずにエラーにする
+    for opt in ["-A", "-B"] {
+        Command::cargo_bin(PRG)?
+            .args(["-m", "1", opt, "4294967295", "2020"])
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(predicate::str::contains(
+                r#"month count "4294967295" not in the range 0 through 119987"#,
+            ));
+    }
+
+    // 上限以内でも、範囲の端が9999年を超えるならエラーにする
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-A", "119987", "2020"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains(
+            r#"year "12018" not in the range 1 through 9999"#,
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-B", "119987", "2020"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains(
+            r#"year "-7979" not in the range 1 through 9999"#,
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn month_at_year_limits() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("     January 1"));
+    Command::cargo_bin(PRG)?
+        .args(["-m", "12", "9999"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("   December 9999"));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_y_and_month() -> Result<()> {
@@ -197,6 +298,15 @@ fn test_2_2020_leap_year() -> Result<()> {
     run(&["-m", "2", "2020"], "tests/expected/2-2020.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn test_2_2020_monday() -> Result<()> {
+    run(
+        &["-M", "-m", "2", "2020"],
+        "tests/expected/2-2020.monday.txt",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn test_4_2020() -> Result<()> {
@@ -215,6 +325,41 @@ fn test_2020() -> Result<()> {
     run(&["2020"], "tests/expected/2020.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn test_2015() -> Result<()> {
+    // 2015年2月は4週しかないので、残りの2行を空白で埋めて列をそろえる
+    run(&["2015"], "tests/expected/2015.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_12_2025_after_before() -> Result<()> {
+    // 年をまたいで前後の月を3か月ずつ並べる
+    run(
+        &["-m", "12", "2025", "-A2", "-B1"],
+        "tests/expected/12-2025.A2.B1.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_three_months() -> Result<()> {
+    // -3は-B1 -A1と同じ
+    let output = Command::cargo_bin(PRG)?
+        .args(["-m", "1", "2026", "-B1", "-A1"])
+        .output()?;
+    assert!(output.status.success());
+    let expected = String::from_utf8(output.stdout)?;
+    assert!(expected.starts_with("   December 2025          January 2026"));
+    Command::cargo_bin(PRG)?
+        .args(["-3", "-m", "1", "2026"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn year() -> Result<()> {
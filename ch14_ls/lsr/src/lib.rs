@@ -3,17 +3,67 @@ mod owner;
 use chrono::{DateTime, Local};
 use clap::{App, Arg};
 use owner::Owner;
-use std::{error::Error, fs, os::unix::fs::MetadataExt, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 use tabular::{Row, Table};
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(unix)]
 use users::{get_group_by_gid, get_user_by_uid};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Config {
     paths: Vec<String>,
     long: bool,
     show_hidden: bool,
+    quoting_style: QuotingStyle,
+    comma_format: bool,
+    columns: bool,
+    width: Option<usize>,
+    dereference: bool,
+    time_style: TimeStyle,
+    no_owner: bool,
+    no_group: bool,
+    recursive: bool,
+    sort_by: SortBy,
+    reverse: bool,
+    human_readable: bool,
+    directory: bool,
+    inode: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum QuotingStyle {
+    #[default]
+    Literal,
+    Shell,
+    C,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum SortBy {
+    #[default]
+    Name,
+    Time,
+    Size,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum TimeStyle {
+    #[default]
+    Locale,
+    LongIso,
+    FullIso,
+    Relative,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -40,46 +90,258 @@ pub fn get_args() -> MyResult<Config> {
                 .long("long")
                 .help("Long listing"),
         )
+        .arg(
+            Arg::with_name("recursive")
+                .short("R")
+                .long("recursive")
+                .help("List subdirectories recursively"),
+        )
+        .arg(
+            Arg::with_name("directory")
+                .short("d")
+                .long("directory")
+                .help("List directories themselves, not their contents"),
+        )
+        .arg(
+            Arg::with_name("inode")
+                .short("i")
+                .long("inode")
+                .help("Print the index number of each file"),
+        )
+        .arg(
+            Arg::with_name("human_readable")
+                .short("h")
+                .long("human-readable")
+                .help("With -l, print sizes like 1.0K 234M 2.0G"),
+        )
+        .arg(
+            Arg::with_name("sort_time")
+                .short("t")
+                .help("Sort by modification time, newest first"),
+        )
+        .arg(
+            Arg::with_name("sort_size")
+                .short("S")
+                .help("Sort by file size, largest first")
+                .conflicts_with("sort_time"),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .short("r")
+                .long("reverse")
+                .help("Reverse order while sorting"),
+        )
+        .arg(
+            Arg::with_name("no_owner")
+                .short("g")
+                .help("Like -l, but do not list owner"),
+        )
+        .arg(
+            Arg::with_name("no_group_long")
+                .short("o")
+                .help("Like -l, but do not list group information"),
+        )
+        .arg(
+            Arg::with_name("no_group")
+                .short("G")
+                .long("no-group")
+                .help("In a long listing, don't print group names"),
+        )
+        .arg(
+            Arg::with_name("quoting_style")
+                .long("quoting-style")
+                .value_name("WORD")
+                .help("Use quoting style WORD for entry names")
+                .possible_values(&["literal", "shell", "c"])
+                .default_value("literal"),
+        )
+        .arg(
+            Arg::with_name("comma")
+                .short("m")
+                .help("Fill width with a comma separated list of entries"),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .short("C")
+                .help("List entries by columns")
+                .conflicts_with("one_per_line"),
+        )
+        .arg(
+            Arg::with_name("one_per_line")
+                .short("1")
+                .help("List one entry per line"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .short("w")
+                .long("width")
+                .value_name("COLS")
+                .help("Set output width to COLS"),
+        )
+        .arg(
+            Arg::with_name("dereference")
+                .short("L")
+                .long("dereference")
+                .help("Show information for the target of symbolic links"),
+        )
+        .arg(
+            Arg::with_name("time_style")
+                .long("time-style")
+                .value_name("STYLE")
+                .help("Time format to use with -l")
+                .possible_values(&["locale", "long-iso", "full-iso", "relative"])
+                .default_value("locale"),
+        )
         .get_matches();
 
+    let width = matches
+        .value_of("width")
+        .map(|val| {
+            val.parse()
+                .map_err(|_| format!("invalid line width: '{}'", val))
+        })
+        .transpose()?;
+
+    let quoting_style = match matches.value_of("quoting_style") {
+        Some("shell") => QuotingStyle::Shell,
+        Some("c") => QuotingStyle::C,
+        _ => QuotingStyle::Literal,
+    };
+
+    let time_style = match matches.value_of("time_style") {
+        Some("long-iso") => TimeStyle::LongIso,
+        Some("full-iso") => TimeStyle::FullIso,
+        Some("relative") => TimeStyle::Relative,
+        _ => TimeStyle::Locale,
+    };
+
+    let sort_by = if matches.is_present("sort_time") {
+        SortBy::Time
+    } else if matches.is_present("sort_size") {
+        SortBy::Size
+    } else {
+        SortBy::Name
+    };
+
     Ok(Config {
         paths: matches.values_of_lossy("paths").unwrap(),
-        long: matches.is_present("long"),
+        long: ["long", "no_owner", "no_group_long"]
+            .iter()
+            .any(|name| matches.is_present(name)),
         show_hidden: matches.is_present("all"),
+        quoting_style,
+        comma_format: matches.is_present("comma"),
+        // GNU lsと同じく、端末に出力する場合は既定で列に並べる
+        columns: !matches.is_present("one_per_line")
+            && (matches.is_present("columns") || io::stdout().is_terminal()),
+        width,
+        dereference: matches.is_present("dereference"),
+        time_style,
+        no_owner: matches.is_present("no_owner"),
+        no_group: matches.is_present("no_group") || matches.is_present("no_group_long"),
+        recursive: matches.is_present("recursive"),
+        sort_by,
+        reverse: matches.is_present("reverse"),
+        human_readable: matches.is_present("human_readable"),
+        directory: matches.is_present("directory"),
+        inode: matches.is_present("inode"),
     })
 }
 
-pub fn run(config: Config) -> MyResult<()> {
-    let paths = find_files(&config.paths, config.show_hidden)?;
+/// 表示できなかったパスがあればfalseを返す
+pub fn run(config: Config) -> MyResult<bool> {
+    let mut failed = false;
+    // -dの場合はディレクトリの中を見ないので-Rも無視する
+    if config.recursive && !config.directory {
+        // GNU lsと同じく、ディレクトリごとに「dir:」の見出しを付けて空行で区切る
+        for (i, (dir, paths)) in find_sections(&config.paths, config.show_hidden, &mut failed)?
+            .iter()
+            .enumerate()
+        {
+            if i > 0 {
+                println!();
+            }
+            if let Some(dir) = dir {
+                println!("{}:", dir.display());
+            }
+            // 空のディレクトリは見出しだけを表示する。-lの場合は「total 0」も表示する
+            if !paths.is_empty() || config.long {
+                print_paths(paths, &config, dir.is_some())?;
+            }
+        }
+    } else {
+        let paths = find_files(
+            &config.paths,
+            config.show_hidden,
+            config.directory,
+            &mut failed,
+        )?;
+        // ディレクトリの中身だけを表示する場合に合計ブロック数を表示する。
+        // ファイルの引数と混ざる場合はGNU lsと同じく表示しない
+        let listing_dir =
+            !config.directory && config.paths.iter().all(|name| Path::new(name).is_dir());
+        print_paths(&paths, &config, listing_dir)?;
+    }
+    Ok(!failed)
+}
 
+fn print_paths(paths: &[PathBuf], config: &Config, show_total: bool) -> MyResult<()> {
+    let paths = &sort_paths(paths, config.sort_by, config.reverse);
     if config.long {
-        println!("{}", format_output(&paths)?);
+        if show_total {
+            if let Some(total) = total_blocks(paths, config) {
+                println!("total {}", total);
+            }
+        }
+        if !paths.is_empty() {
+            println!("{}", format_output(paths, config)?);
+        }
+    } else if config.comma_format {
+        let names: Vec<_> = paths
+            .iter()
+            .map(|path| short_name(path, config))
+            .collect();
+        print!(
+            "{}",
+            format_comma(&names, config.width.unwrap_or_else(term_width))
+        );
+    } else if config.columns {
+        let names: Vec<_> = paths
+            .iter()
+            .map(|path| short_name(path, config))
+            .collect();
+        print!(
+            "{}",
+            format_columns(&names, config.width.unwrap_or_else(term_width))
+        );
     } else {
         for path in paths {
-            println!("{}", path.display());
+            println!("{}", short_name(path, config));
         }
     }
     Ok(())
 }
 
-fn find_files(paths: &[String], show_hidden: bool) -> MyResult<Vec<PathBuf>> {
+/// 読めないパスはエラーを表示して飛ばし、failedをtrueにする。
+/// list_dirsがtrue（-d）の場合はディレクトリも中身ではなくそれ自体を返す
+fn find_files(
+    paths: &[String],
+    show_hidden: bool,
+    list_dirs: bool,
+    failed: &mut bool,
+) -> MyResult<Vec<PathBuf>> {
     let mut results = vec![];
 
     for name in paths {
-        match fs::metadata(name) {
-            Err(e) => eprintln!("{}: {}", name, e),
+        // 参照先のないシンボリックリンクもリンク自体として扱う
+        match fs::metadata(name).or_else(|_| fs::symlink_metadata(name)) {
+            Err(e) => report(Path::new(name), &e, failed),
             Ok(meta) => {
-                if meta.is_dir() {
-                    for entry in fs::read_dir(name)? {
-                        let entry = entry?;
-                        let path = entry.path();
-                        let is_hidden = path.file_name().map_or(false, |file_name| {
-                            file_name.to_string_lossy().starts_with('.')
-                        });
-                        if !is_hidden || show_hidden {
-                            results.push(path);
-                        }
+                if meta.is_dir() && !list_dirs {
+                    if show_hidden {
+                        results.extend(dot_entries(Path::new(name)));
                     }
+                    results.extend(read_entries(Path::new(name), show_hidden, failed));
                 } else {
                     results.push(PathBuf::from(name));
                 }
@@ -90,6 +352,123 @@ fn find_files(paths: &[String], show_hidden: bool) -> MyResult<Vec<PathBuf>> {
     Ok(results)
 }
 
+/// メタデータを一度だけ取得して並べ替える。
+/// 時刻とサイズは大きいものから順に並べ、同じ場合は名前の順にする
+fn sort_paths(paths: &[PathBuf], sort_by: SortBy, reverse: bool) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = paths
+        .iter()
+        .map(|path| (path, fs::symlink_metadata(path).ok()))
+        .collect();
+
+    entries.sort_by(|(a, meta_a), (b, meta_b)| {
+        let mtime = |meta: &Option<fs::Metadata>| meta.as_ref().and_then(|m| m.modified().ok());
+        let size = |meta: &Option<fs::Metadata>| meta.as_ref().map(|m| m.len());
+        match sort_by {
+            SortBy::Name => Ordering::Equal,
+            SortBy::Time => mtime(meta_b).cmp(&mtime(meta_a)),
+            SortBy::Size => size(meta_b).cmp(&size(meta_a)),
+        }
+        .then_with(|| a.cmp(b))
+    });
+
+    if reverse {
+        entries.reverse();
+    }
+    entries.into_iter().map(|(path, _)| path.clone()).collect()
+}
+
+fn report(path: &Path, e: &io::Error, failed: &mut bool) {
+    eprintln!("lsr: {}: {}", path.display(), e);
+    *failed = true;
+}
+
+fn read_entries(dir: &Path, show_hidden: bool, failed: &mut bool) -> Vec<PathBuf> {
+    let mut results = vec![];
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report(dir, &e, failed);
+            return results;
+        }
+    };
+
+    for entry in entries {
+        // 読んでいる間に削除されたエントリなどは飛ばす
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => {
+                report(dir, &e, failed);
+                continue;
+            }
+        };
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'));
+        if !is_hidden || show_hidden {
+            results.push(path);
+        }
+    }
+    results
+}
+
+/// -aで表示する「.」と「..」。read_dirは返さないので自分で加える
+fn dot_entries(dir: &Path) -> [PathBuf; 2] {
+    [dir.join("."), dir.join("..")]
+}
+
+/// -Rのために、ディレクトリとその中のエントリの組を深さ優先の順に返す。
+/// 引数で指定したファイルは見出しのない最初の組にまとめる
+fn find_sections(
+    paths: &[String],
+    show_hidden: bool,
+    failed: &mut bool,
+) -> MyResult<Vec<(Option<PathBuf>, Vec<PathBuf>)>> {
+    let mut files = vec![];
+    let mut sections = vec![];
+
+    for name in paths {
+        // 参照先のないシンボリックリンクもリンク自体として扱う
+        match fs::metadata(name).or_else(|_| fs::symlink_metadata(name)) {
+            Err(e) => report(Path::new(name), &e, failed),
+            Ok(meta) if meta.is_dir() => {
+                walk_dir(Path::new(name), show_hidden, &mut sections, failed)
+            }
+            Ok(_) => files.push(PathBuf::from(name)),
+        }
+    }
+
+    if !files.is_empty() {
+        sections.insert(0, (None, files));
+    }
+    Ok(sections)
+}
+
+fn walk_dir(
+    dir: &Path,
+    show_hidden: bool,
+    sections: &mut Vec<(Option<PathBuf>, Vec<PathBuf>)>,
+    failed: &mut bool,
+) {
+    let mut entries = read_entries(dir, show_hidden, failed);
+    entries.sort();
+
+    // シンボリックリンクのディレクトリはたどらない
+    let subdirs: Vec<_> = entries
+        .iter()
+        .filter(|path| fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir()))
+        .cloned()
+        .collect();
+
+    // 「.」と「..」はたどらないように、サブディレクトリを探した後で加える
+    if show_hidden {
+        entries.extend(dot_entries(dir));
+    }
+    sections.push((Some(dir.to_path_buf()), entries));
+    for subdir in subdirs {
+        walk_dir(&subdir, show_hidden, sections, failed);
+    }
+}
+
 /// 0o500のような8進数と[`Owner`]を指定すると、
 /// 「r-x」のような文字列を返す
 pub fn mk_triple(mode: u32, owner: Owner) -> String {
@@ -102,47 +481,319 @@ pub fn mk_triple(mode: u32, owner: Owner) -> String {
     )
 }
 
-fn format_output(paths: &[PathBuf]) -> MyResult<String> {
-    let fmt = "{:<}{:<}  {:>}  {:<}  {:<}  {:<}  {:<}  {:<}";
-    let mut table = Table::new(fmt);
+/// 1024を単位として「1.0K」や「340M」のように表示する。
+/// GNU lsと同じく切り上げ、10未満の場合だけ小数点以下1桁を表示する
+fn human_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
 
-    for path in paths {
-        let metadata = path.metadata()?;
+    let mut size = bytes as f64;
+    for unit in ["K", "M", "G", "T", "P", "E"] {
+        size /= 1024.0;
+        let rounded = if size < 10.0 {
+            (size * 10.0).ceil() / 10.0
+        } else {
+            size.ceil()
+        };
+        // 切り上げて1024になる場合は次の単位で表示する
+        if rounded < 1024.0 || unit == "E" {
+            return if rounded < 10.0 {
+                format!("{:.1}{}", rounded, unit)
+            } else {
+                format!("{:.0}{}", rounded, unit)
+            };
+        }
+    }
+    unreachable!()
+}
 
-        let uid = metadata.uid();
-        let user = get_user_by_uid(uid)
-            .map(|u| u.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| uid.to_string());
+/// 端末の幅を返す。端末でない場合はGNU lsと同じく80とする
+fn term_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(w), _)| w as usize)
+        .unwrap_or(80)
+}
 
-        let gid = metadata.gid();
-        let group = get_group_by_gid(gid)
-            .map(|g| g.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| gid.to_string());
+/// 名前を「, 」で区切って並べ、幅を超える場合は折り返す
+fn format_comma(names: &[String], width: usize) -> String {
+    let mut output = String::new();
+    let mut pos = 0;
 
-        let file_type = if path.is_dir() { "d" } else { "-" };
+    for (i, name) in names.iter().enumerate() {
+        let len = name.chars().count();
+        if i > 0 {
+            // 行末のカンマも幅に含める
+            let trailing = if i + 1 < names.len() { 1 } else { 0 };
+            if pos + 2 + len + trailing > width {
+                output.push_str(",\n");
+                pos = 0;
+            } else {
+                output.push_str(", ");
+                pos += 2;
+            }
+        }
+        output.push_str(name);
+        pos += len;
+    }
 
-        let perms = format_mode(metadata.mode());
+    if !names.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+/// GNU lsと同じく、上から下、左から右の順に列に並べる。
+/// 列の間は2文字空け、行の長さがwidthより短くなる最大の列数を選ぶ
+fn format_columns(names: &[String], width: usize) -> String {
+    if names.is_empty() {
+        return String::new();
+    }
+
+    let lens: Vec<_> = names.iter().map(|name| name.chars().count()).collect();
+    // 1文字の名前と2文字の間隔の列が最も狭い
+    let max_cols = names.len().min(width / 3).max(1);
+    let (rows, widths) = (1..=max_cols)
+        .rev()
+        .find_map(|cols| {
+            let rows = names.len().div_ceil(cols);
+            let widths: Vec<_> = lens
+                .chunks(rows)
+                .map(|col| *col.iter().max().unwrap())
+                .collect();
+            let line_len = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+            (widths.len() == 1 || line_len < width).then_some((rows, widths))
+        })
+        .unwrap();
+
+    let mut output = String::new();
+    for row in 0..rows {
+        let cells: Vec<_> = (0..widths.len())
+            .filter_map(|col| names.get(col * rows + row).map(|name| (name, widths[col])))
+            .collect();
+        for (i, (name, col_width)) in cells.iter().enumerate() {
+            if i + 1 < cells.len() {
+                output.push_str(&format!("{:<width$}  ", name, width = col_width));
+            } else {
+                output.push_str(name);
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// -lでない場合に表示する名前。-iの場合は前にinode番号を付ける
+fn short_name(path: &Path, config: &Config) -> String {
+    let name = quote_name(path, config.quoting_style);
+    if config.inode {
+        format!("{} {}", inode(path, config.dereference), name)
+    } else {
+        name
+    }
+}
+
+/// 情報を読めない場合はGNU lsと同じく「?」を返す
+#[cfg(unix)]
+fn inode(path: &Path, dereference: bool) -> String {
+    long_metadata(path, dereference).map_or_else(|_| "?".to_string(), |m| m.ino().to_string())
+}
+
+/// Windowsにはinode番号がない
+#[cfg(windows)]
+fn inode(_path: &Path, _dereference: bool) -> String {
+    "?".to_string()
+}
+
+/// パスを[`QuotingStyle`]に従って表示用の文字列に変換する
+fn quote_name(path: &Path, style: QuotingStyle) -> String {
+    let name = path.display().to_string();
+    match style {
+        QuotingStyle::Literal => name,
+        QuotingStyle::Shell => {
+            let is_safe = |c: char| c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c);
+            if !name.is_empty() && name.chars().all(is_safe) {
+                name
+            } else {
+                format!("'{}'", name.replace('\'', r"'\''"))
+            }
+        }
+        QuotingStyle::C => {
+            let mut quoted = String::from("\"");
+            for c in name.chars() {
+                match c {
+                    '"' => quoted.push_str("\\\""),
+                    '\\' => quoted.push_str("\\\\"),
+                    '\n' => quoted.push_str("\\n"),
+                    '\t' => quoted.push_str("\\t"),
+                    '\r' => quoted.push_str("\\r"),
+                    c if c.is_control() => quoted.push_str(&format!("\\{:03o}", c as u32)),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        }
+    }
+}
 
-        let modified: DateTime<Local> = DateTime::from(metadata.modified()?);
+fn format_output(paths: &[PathBuf], config: &Config) -> MyResult<String> {
+    let fmt = format!("{}  {{:<}}  {{:<}}  {{:<}}", leading_format(config));
+    let mut table = Table::new(&fmt);
+
+    for path in paths {
+        let metadata = long_metadata(path, config.dereference)?;
+
+        let file_type = if metadata.is_dir() {
+            "d"
+        } else if metadata.file_type().is_symlink() {
+            "l"
+        } else {
+            "-"
+        };
+
+        let modified = format_time(metadata.modified()?, config.time_style);
+
+        // GNU lsと同じく「リンク -> 参照先」と表示する
+        let mut name = quote_name(path, config.quoting_style);
+        if metadata.file_type().is_symlink() {
+            name = format!("{} -> {}", name, fs::read_link(path)?.display());
+        }
 
         table.add_row(
-            Row::new()
-                .with_cell(file_type)
-                .with_cell(perms)
-                .with_cell(metadata.nlink())
-                .with_cell(user)
-                .with_cell(group)
-                .with_cell(metadata.len())
-                .with_cell(modified.format("%b %d %y %H:%M"))
-                .with_cell(path.display()),
+            leading_cells(&metadata, file_type, config)
+                .with_cell(if config.human_readable {
+                    human_size(metadata.len())
+                } else {
+                    metadata.len().to_string()
+                })
+                .with_cell(modified)
+                .with_cell(name),
         );
     }
 
     Ok(format!("{}", table))
 }
 
+/// -Lの場合だけシンボリックリンクの参照先の情報を返す。
+/// 参照先がない場合はリンク自体の情報を返す
+fn long_metadata(path: &Path, dereference: bool) -> io::Result<fs::Metadata> {
+    if dereference {
+        fs::metadata(path).or_else(|_| fs::symlink_metadata(path))
+    } else {
+        fs::symlink_metadata(path)
+    }
+}
+
+/// GNU lsと同じく、512バイト単位のブロック数の合計を1Kバイト単位に切り上げて返す。
+/// -hの場合は読みやすい単位で返す
+#[cfg(unix)]
+fn total_blocks(paths: &[PathBuf], config: &Config) -> Option<String> {
+    let blocks: u64 = paths
+        .iter()
+        .filter_map(|path| long_metadata(path, config.dereference).ok())
+        .map(|metadata| metadata.blocks())
+        .sum();
+    Some(if config.human_readable {
+        human_size(blocks * 512)
+    } else {
+        blocks.div_ceil(2).to_string()
+    })
+}
+
+/// Windowsにはブロック数がないので合計を表示しない
+#[cfg(windows)]
+fn total_blocks(_paths: &[PathBuf], _config: &Config) -> Option<String> {
+    None
+}
+
+/// サイズより前の列の書式。表示しない列は書式からも除いて、列の間隔をそろえる
+#[cfg(unix)]
+fn leading_format(config: &Config) -> String {
+    let mut fmt = String::from(if config.inode { "{:>} " } else { "" });
+    fmt.push_str("{:<}{:<}  {:>}");
+    if !config.no_owner {
+        fmt.push_str("  {:<}");
+    }
+    if !config.no_group {
+        fmt.push_str("  {:<}");
+    }
+    fmt
+}
+
+/// inode番号、種類、パーミッション、リンク数、所有者、グループの列
+#[cfg(unix)]
+fn leading_cells(metadata: &fs::Metadata, file_type: &str, config: &Config) -> Row {
+    let mut row = Row::new();
+    if config.inode {
+        row = row.with_cell(metadata.ino());
+    }
+    row = row
+        .with_cell(file_type)
+        .with_cell(format_mode(metadata.mode()))
+        .with_cell(metadata.nlink());
+
+    if !config.no_owner {
+        let uid = metadata.uid();
+        row = row.with_cell(
+            get_user_by_uid(uid)
+                .map(|u| u.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| uid.to_string()),
+        );
+    }
+    if !config.no_group {
+        let gid = metadata.gid();
+        row = row.with_cell(
+            get_group_by_gid(gid)
+                .map(|g| g.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| gid.to_string()),
+        );
+    }
+    row
+}
+
+/// Windowsにはパーミッションや所有者がないので、種類の列だけを表示する
+#[cfg(windows)]
+fn leading_format(_config: &Config) -> String {
+    String::from("{:<}")
+}
+
+#[cfg(windows)]
+fn leading_cells(_metadata: &fs::Metadata, file_type: &str, _config: &Config) -> Row {
+    Row::new().with_cell(file_type)
+}
+
+/// 更新日時を[`TimeStyle`]に従って表示用の文字列に変換する
+fn format_time(modified: SystemTime, style: TimeStyle) -> String {
+    let datetime: DateTime<Local> = DateTime::from(modified);
+    match style {
+        TimeStyle::Locale => datetime.format("%b %d %y %H:%M").to_string(),
+        TimeStyle::LongIso => datetime.format("%Y-%m-%d %H:%M").to_string(),
+        TimeStyle::FullIso => datetime.format("%Y-%m-%d %H:%M:%S%.9f %z").to_string(),
+        TimeStyle::Relative => match SystemTime::now().duration_since(modified) {
+            Ok(elapsed) => format_relative(elapsed),
+            Err(_) => "in the future".to_string(),
+        },
+    }
+}
+
+/// 経過時間を「3 days ago」のような文字列で返す
+fn format_relative(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (num, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (secs / 60, "minute"),
+        3_600..=86_399 => (secs / 3_600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+    format!("{} {}{} ago", num, unit, if num == 1 { "" } else { "s" })
+}
+
 /// 0o751のような8進数でファイルモードを指定すると、
 /// 「rwxr-x--x」のような文字列を返す。
+#[cfg(unix)]
 fn format_mode(mode: u32) -> String {
     format!(
         "{}{}{}",
@@ -154,10 +805,22 @@ fn format_mode(mode: u32) -> String {
 
 #[cfg(test)]
 mod test {
-    use super::{find_files, format_mode, format_output, mk_triple, Owner};
-    use std::path::PathBuf;
+    use super::{
+        find_files, format_columns, format_comma, format_relative, human_size, mk_triple,
+        quote_name, sort_paths, Config, Owner, QuotingStyle, SortBy,
+    };
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+    #[cfg(unix)]
+    use {
+        super::{format_mode, format_output, total_blocks},
+        std::os::unix::fs::MetadataExt,
+        std::path::PathBuf,
+    };
 
     // テストのためのヘルパー関数
+    #[cfg(unix)]
     fn long_match(
         line: &str,
         expected_name: &str,
@@ -166,9 +829,9 @@ mod test {
     ) {
         let parts: Vec<_> = line.split_whitespace().collect();
         println!("parts: {:?}", parts);
-        assert!(parts.len() > 0 && parts.len() <= 10);
+        assert!(!parts.is_empty() && parts.len() <= 10);
 
-        let perms = parts.get(0).unwrap();
+        let perms = parts.first().unwrap();
         assert_eq!(perms, &expected_perms);
 
         if let Some(size) = expected_size {
@@ -180,10 +843,65 @@ mod test {
         assert_eq!(display_name, &expected_name);
     }
 
+    // リンク数の列は10進数で表示する
+    #[cfg(unix)]
+    fn nlink_match(line: &str, path: &str) {
+        let parts: Vec<_> = line.split_whitespace().collect();
+        let nlink = fs::metadata(path).unwrap().nlink();
+        assert_eq!(parts.get(1), Some(&nlink.to_string().as_str()));
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0");
+        assert_eq!(human_size(1023), "1023");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1025), "1.1K");
+        assert_eq!(human_size(340 * 1024 * 1024), "340M");
+        assert_eq!(human_size(1024 * 1024 - 1), "1.0M");
+        assert_eq!(
+            human_size(5 * 1024 * 1024 * 1024 + 300 * 1024 * 1024),
+            "5.3G"
+        );
+    }
+
+    #[test]
+    fn test_sort_paths() {
+        // サイズと更新時刻を指定したファイルを作成する
+        let dir = tempfile::tempdir().unwrap();
+        let now = std::time::SystemTime::now();
+        let mut paths = vec![];
+        for (name, size, age) in [("b.txt", 10, 300), ("a.txt", 30, 100), ("c.txt", 20, 200)] {
+            let path = dir.path().join(name);
+            let file = fs::File::create(&path).unwrap();
+            file.set_len(size).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+            paths.push(path);
+        }
+
+        let names = |sort_by, reverse| -> Vec<String> {
+            sort_paths(&paths, sort_by, reverse)
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(names(SortBy::Name, false), ["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(names(SortBy::Name, true), ["c.txt", "b.txt", "a.txt"]);
+
+        // 新しいものから順に並べる
+        assert_eq!(names(SortBy::Time, false), ["a.txt", "c.txt", "b.txt"]);
+        assert_eq!(names(SortBy::Time, true), ["b.txt", "c.txt", "a.txt"]);
+
+        // 大きいものから順に並べる
+        assert_eq!(names(SortBy::Size, false), ["a.txt", "c.txt", "b.txt"]);
+        assert_eq!(names(SortBy::Size, true), ["b.txt", "c.txt", "a.txt"]);
+    }
+
     #[test]
     fn test_find_files() {
         // ディレクトリにある隠しエントリ以外のエントリを検索する
-        let res = find_files(&["tests/inputs".to_string()], false);
+        let res = find_files(&["tests/inputs".to_string()], false, false, &mut false);
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
@@ -202,7 +920,12 @@ mod test {
         );
 
         // 存在するファイルは、隠しファイルであっても検索できるようにする
-        let res = find_files(&["tests/inputs/.hidden".to_string()], false);
+        let res = find_files(
+            &["tests/inputs/.hidden".to_string()],
+            false,
+            false,
+            &mut false,
+        );
         assert!(res.is_ok());
         let filenames: Vec<_> = res
             .unwrap()
@@ -218,6 +941,8 @@ mod test {
                 "tests/inputs/dir".to_string(),
             ],
             false,
+            false,
+            &mut false,
         );
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
@@ -232,10 +957,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_find_files_directory() {
+        // -dの場合はディレクトリの中身ではなくディレクトリ自体を返す
+        let res = find_files(
+            &[
+                "tests/inputs".to_string(),
+                "tests/inputs/fox.txt".to_string(),
+            ],
+            true,
+            true,
+            &mut false,
+        );
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            [Path::new("tests/inputs"), Path::new("tests/inputs/fox.txt")]
+        );
+    }
+
     #[test]
     fn test_find_files_hidden() {
-        // ディレクトリにあるすべてのエントリを検索する
-        let res = find_files(&["tests/inputs".to_string()], true);
+        // ディレクトリにあるすべてのエントリを「.」と「..」も含めて検索する
+        let res = find_files(&["tests/inputs".to_string()], true, false, &mut false);
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
@@ -246,6 +990,8 @@ mod test {
         assert_eq!(
             filenames,
             [
+                "tests/inputs/.",
+                "tests/inputs/..",
                 "tests/inputs/.hidden",
                 "tests/inputs/bustle.txt",
                 "tests/inputs/dir",
@@ -256,17 +1002,19 @@ mod test {
     }
 
     #[test]
+    #[cfg(unix)]
     fn test_format_mode() {
         assert_eq!(format_mode(0o755), "rwxr-xr-x");
         assert_eq!(format_mode(0o421), "r---w---x");
     }
 
     #[test]
+    #[cfg(unix)]
     fn test_format_output_one() {
         let bustle_path = "tests/inputs/bustle.txt";
         let bustle = PathBuf::from(bustle_path);
 
-        let res = format_output(&[bustle]);
+        let res = format_output(&[bustle], &Config::default());
         assert!(res.is_ok());
 
         let out = res.unwrap();
@@ -274,15 +1022,20 @@ mod test {
         assert_eq!(lines.len(), 1);
 
         let line1 = lines.first().unwrap();
-        long_match(&line1, bustle_path, "-rw-r--r--", Some("193"));
+        long_match(line1, bustle_path, "-rw-r--r--", Some("193"));
+        nlink_match(line1, bustle_path);
     }
 
     #[test]
+    #[cfg(unix)]
     fn test_format_output_two() {
-        let res = format_output(&[
-            PathBuf::from("tests/inputs/dir"),
-            PathBuf::from("tests/inputs/empty.txt"),
-        ]);
+        let res = format_output(
+            &[
+                PathBuf::from("tests/inputs/dir"),
+                PathBuf::from("tests/inputs/empty.txt"),
+            ],
+            &Config::default(),
+        );
         assert!(res.is_ok());
 
         let out = res.unwrap();
@@ -292,14 +1045,124 @@ mod test {
 
         let empty_line = lines.remove(0);
         long_match(
-            &empty_line,
+            empty_line,
             "tests/inputs/empty.txt",
             "-rw-r--r--",
             Some("0"),
         );
+        nlink_match(empty_line, "tests/inputs/empty.txt");
 
         let dir_line = lines.remove(0);
-        long_match(&dir_line, "tests/inputs/dir", "drwxr-xr-x", None);
+        long_match(dir_line, "tests/inputs/dir", "drwxr-xr-x", None);
+        nlink_match(dir_line, "tests/inputs/dir");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_format_output_symlink() {
+        let link_path = "tests/links/bustle.link";
+        let link = PathBuf::from(link_path);
+
+        // リンク自体のサイズは参照先のパスの長さになる
+        let out = format_output(std::slice::from_ref(&link), &Config::default()).unwrap();
+        let (line, target) = out.trim_end().split_once(" -> ").unwrap();
+        long_match(line, link_path, "lrwxrwxrwx", Some("20"));
+        assert_eq!(target, "../inputs/bustle.txt");
+
+        // -Lの場合は参照先のファイルのサイズを表示する
+        let config = Config {
+            dereference: true,
+            ..Default::default()
+        };
+        let out = format_output(&[link], &config).unwrap();
+        long_match(out.trim_end(), link_path, "-rw-r--r--", Some("193"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_format_output_broken_symlink() {
+        // 参照先のないシンボリックリンクも種類「l」として表示する
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("broken.link");
+        std::os::unix::fs::symlink("does-not-exist", &link).unwrap();
+
+        let dir_name = dir.path().display().to_string();
+        let paths = find_files(&[dir_name], false, false, &mut false).unwrap();
+        assert_eq!(paths, std::slice::from_ref(&link));
+
+        for dereference in [false, true] {
+            let config = Config {
+                dereference,
+                ..Default::default()
+            };
+            let out = format_output(&paths, &config).unwrap();
+            let (line, target) = out.trim_end().split_once(" -> ").unwrap();
+            long_match(line, &link.display().to_string(), "lrwxrwxrwx", None);
+            assert_eq!(target, "does-not-exist");
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_total_blocks() {
+        // 512バイト単位のブロック数を1Kバイト単位に切り上げて合計する
+        let paths = [
+            PathBuf::from("tests/inputs/bustle.txt"),
+            PathBuf::from("tests/inputs/fox.txt"),
+            PathBuf::from("tests/inputs/empty.txt"),
+        ];
+        let blocks: u64 = paths
+            .iter()
+            .map(|path| fs::metadata(path).unwrap().blocks())
+            .sum();
+        let config = Config::default();
+        assert_eq!(
+            total_blocks(&paths, &config),
+            Some(blocks.div_ceil(2).to_string())
+        );
+
+        // 空のファイルはブロックを使わない
+        assert_eq!(
+            total_blocks(&[PathBuf::from("tests/inputs/empty.txt")], &config),
+            Some("0".to_string())
+        );
+        assert_eq!(total_blocks(&[], &config), Some("0".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_format_output_no_owner_group() {
+        // -g -oの場合は所有者とグループの列を詰めて表示する
+        let config = Config {
+            no_owner: true,
+            no_group: true,
+            ..Default::default()
+        };
+        let res = format_output(
+            &[
+                PathBuf::from("tests/inputs/dir"),
+                PathBuf::from("tests/inputs/bustle.txt"),
+            ],
+            &config,
+        );
+        assert!(res.is_ok());
+
+        let out = res.unwrap();
+        let lines: Vec<&str> = out.split("\n").filter(|s| !s.is_empty()).collect();
+        assert_eq!(lines.len(), 2);
+
+        let dir_parts: Vec<_> = lines[0].split_whitespace().collect();
+        let bustle_parts: Vec<_> = lines[1].split_whitespace().collect();
+        assert_eq!(dir_parts[0], "drwxr-xr-x");
+        assert_eq!(bustle_parts[0], "-rw-r--r--");
+        assert_eq!(bustle_parts[2], "193");
+
+        // 名前の列の位置がそろっている
+        assert_eq!(
+            lines[0].find("tests/inputs/dir"),
+            lines[1].find("tests/inputs/bustle.txt")
+        );
+        assert!(!lines[1].contains("   193"));
     }
 
     #[test]
@@ -309,4 +1172,117 @@ mod test {
         assert_eq!(mk_triple(0o751, Owner::Other), "--x");
         assert_eq!(mk_triple(0o600, Owner::Other), "---");
     }
+
+    #[test]
+    fn test_quote_name() {
+        let space = Path::new("foo bar.txt");
+        let newline = Path::new("foo\nbar.txt");
+        let plain = Path::new("tests/inputs/fox.txt");
+
+        // literalはそのまま表示する
+        assert_eq!(quote_name(space, QuotingStyle::Literal), "foo bar.txt");
+        assert_eq!(quote_name(newline, QuotingStyle::Literal), "foo\nbar.txt");
+
+        // shellは必要な場合のみクォートする
+        assert_eq!(
+            quote_name(plain, QuotingStyle::Shell),
+            "tests/inputs/fox.txt"
+        );
+        assert_eq!(quote_name(space, QuotingStyle::Shell), "'foo bar.txt'");
+        assert_eq!(quote_name(newline, QuotingStyle::Shell), "'foo\nbar.txt'");
+        assert_eq!(
+            quote_name(Path::new("it's"), QuotingStyle::Shell),
+            r"'it'\''s'"
+        );
+
+        // cは常にダブルクォートで囲み、制御文字をエスケープする
+        assert_eq!(quote_name(space, QuotingStyle::C), r#""foo bar.txt""#);
+        assert_eq!(quote_name(newline, QuotingStyle::C), r#""foo\nbar.txt""#);
+        assert_eq!(
+            quote_name(Path::new("a\u{1}b"), QuotingStyle::C),
+            r#""a\001b""#
+        );
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let secs = Duration::from_secs;
+
+        // 1分未満は「just now」
+        assert_eq!(format_relative(secs(0)), "just now");
+        assert_eq!(format_relative(secs(59)), "just now");
+
+        // 分・時間・日の単位で切り捨てて表示する
+        assert_eq!(format_relative(secs(60)), "1 minute ago");
+        assert_eq!(format_relative(secs(59 * 60 + 59)), "59 minutes ago");
+        assert_eq!(format_relative(secs(3_600)), "1 hour ago");
+        assert_eq!(format_relative(secs(2 * 3_600 + 1)), "2 hours ago");
+        assert_eq!(format_relative(secs(86_400)), "1 day ago");
+        assert_eq!(format_relative(secs(3 * 86_400)), "3 days ago");
+
+        // それより古い場合は月・年の単位で表示する
+        assert_eq!(format_relative(secs(45 * 86_400)), "1 month ago");
+        assert_eq!(format_relative(secs(800 * 86_400)), "2 years ago");
+    }
+
+    #[test]
+    fn test_format_comma() {
+        let mut names: Vec<_> = find_files(&["tests/inputs".to_string()], false, false, &mut false)
+            .unwrap()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        names.sort();
+
+        // 幅に収まる場合は1行にカンマ区切りで並べる
+        assert_eq!(
+            format_comma(&names, 200),
+            "tests/inputs/bustle.txt, tests/inputs/dir, tests/inputs/empty.txt, \
+             tests/inputs/fox.txt\n"
+        );
+
+        // 幅を超える場合は行末にカンマを残して折り返す
+        let out = format_comma(&names, 50);
+        assert_eq!(
+            out,
+            "tests/inputs/bustle.txt, tests/inputs/dir,\n\
+             tests/inputs/empty.txt, tests/inputs/fox.txt\n"
+        );
+        assert!(out.lines().all(|line| line.len() <= 50));
+
+        // 空の場合は何も表示しない
+        assert_eq!(format_comma(&[], 80), "");
+    }
+
+    #[test]
+    fn test_format_columns() {
+        let names: Vec<_> = ["a", "bb", "ccc", "dddd", "e"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        // 幅に収まる場合は1行に並べる
+        assert_eq!(format_columns(&names, 80), "a  bb  ccc  dddd  e\n");
+
+        // 上から下、左から右の順に並べ、列の幅は最も長い名前に合わせる
+        assert_eq!(format_columns(&names, 12), "a   ccc   e\nbb  dddd\n");
+
+        // 1列にも収まらない場合は1行に1つずつ表示する
+        assert_eq!(format_columns(&names, 3), "a\nbb\nccc\ndddd\ne\n");
+
+        // 最も長い名前より広ければ、どの行も指定した幅を超えない
+        let mut names: Vec<_> = find_files(&["tests/inputs".to_string()], true, false, &mut false)
+            .unwrap()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        names.sort();
+        for width in [30, 40, 60, 80] {
+            let out = format_columns(&names, width);
+            assert_eq!(out.split_whitespace().count(), names.len());
+            assert!(out.lines().all(|line| line.chars().count() < width));
+        }
+
+        assert_eq!(format_columns(&[], 80), "");
+    }
 }
@@ -30,11 +30,11 @@ fn gen_bad_file() -> String {
 #[test]
 fn bad_file() -> Result<()> {
     let bad = gen_bad_file();
-    let expected = format!("{}: No such file or directory (os error 2)", &bad);
+    let expected = format!("lsr: {}: No such file or directory (os error 2)", &bad);
     Command::cargo_bin(PRG)?
         .arg(&bad)
         .assert()
-        .success()
+        .failure()
         .stderr(predicate::str::contains(expected));
     Ok(())
 }
@@ -61,6 +61,7 @@ fn run_short(arg: &str) -> Result<()> {
 }
 
 // --------------------------------------------------
+#[cfg(unix)]
 fn run_long(filename: &str, permissions: &str, size: &str) -> Result<()> {
     let cmd = Command::cargo_bin(PRG)?
         .args(["--long", filename])
@@ -81,6 +82,7 @@ fn empty() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn empty_long() -> Result<()> {
     run_long(EMPTY, "-rw-r--r--", "0")
 }
@@ -92,6 +94,7 @@ fn bustle() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn bustle_long() -> Result<()> {
     run_long(BUSTLE, "-rw-r--r--", "193")
 }
@@ -103,6 +106,7 @@ fn fox() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn fox_long() -> Result<()> {
     run_long(FOX, "-rw-------", "45")
 }
@@ -114,6 +118,7 @@ fn hidden() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn hidden_long() -> Result<()> {
     run_long(HIDDEN, "-rw-r--r--", "0")
 }
@@ -148,6 +153,8 @@ fn dir1_all() -> Result<()> {
     dir_short(
         &["tests/inputs", "--all"],
         &[
+            "tests/inputs/.",
+            "tests/inputs/..",
             "tests/inputs/empty.txt",
             "tests/inputs/bustle.txt",
             "tests/inputs/fox.txt",
@@ -157,6 +164,34 @@ fn dir1_all() -> Result<()> {
     )
 }
 
+#[test]
+fn dir1_one_per_line() -> Result<()> {
+    // -1の場合は1行に1つずつ表示する
+    Command::cargo_bin(PRG)?
+        .args(["-1", "tests/inputs"])
+        .assert()
+        .success()
+        .stdout(
+            "tests/inputs/bustle.txt\ntests/inputs/dir\n\
+             tests/inputs/empty.txt\ntests/inputs/fox.txt\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn dir1_columns() -> Result<()> {
+    // -Cの場合は端末でなくても列に並べる
+    Command::cargo_bin(PRG)?
+        .args(["-C", "-w", "60", "tests/inputs"])
+        .assert()
+        .success()
+        .stdout(
+            "tests/inputs/bustle.txt  tests/inputs/empty.txt\n\
+             tests/inputs/dir         tests/inputs/fox.txt\n",
+        );
+    Ok(())
+}
+
 #[test]
 fn dir2() -> Result<()> {
     dir_short(&["tests/inputs/dir"], &["tests/inputs/dir/spiders.txt"])
@@ -166,23 +201,53 @@ fn dir2() -> Result<()> {
 fn dir2_all() -> Result<()> {
     dir_short(
         &["-a", "tests/inputs/dir"],
-        &["tests/inputs/dir/spiders.txt", "tests/inputs/dir/.gitkeep"],
+        &[
+            "tests/inputs/dir/.",
+            "tests/inputs/dir/..",
+            "tests/inputs/dir/spiders.txt",
+            "tests/inputs/dir/.gitkeep",
+        ],
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn long_file_and_dir_no_total() -> Result<()> {
+    // ファイルとディレクトリを混ぜて指定した場合は合計ブロック数を表示しない
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["-l", "tests/inputs/fox.txt", "tests/inputs/dir"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    assert!(!stdout.contains("total "), "{}", stdout);
+    assert!(stdout.contains("tests/inputs/fox.txt"), "{}", stdout);
+    assert!(stdout.contains("tests/inputs/dir/spiders.txt"), "{}", stdout);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[allow(suspicious_double_ref_op)]
+#[cfg(unix)]
 fn dir_long(args: &[&str], expected: &[(&str, &str, &str)]) -> Result<()> {
     let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
     let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
-    let lines: Vec<&str> = stdout.split('\n').filter(|s| !s.is_empty()).collect();
+    let mut lines = stdout.split('\n').filter(|s| !s.is_empty());
+
+    // ディレクトリの中身の前に合計ブロック数を表示する
+    let total = lines.next().unwrap();
+    assert!(total.starts_with("total "), "{}", total);
+    let lines: Vec<&str> = lines.collect();
     assert_eq!(lines.len(), expected.len());
 
     let mut check = vec![];
     for line in lines {
         let parts: Vec<&str> = line.split_whitespace().collect();
         let path = parts.last().unwrap().clone();
-        let permissions = parts.first().unwrap().clone();
+        let mut permissions = parts.first().unwrap().clone();
+        // 「.」と「..」のパーミッションはチェックアウトした環境によって変わるので種類だけを見る
+        if path.ends_with("/.") || path.ends_with("/..") {
+            permissions = &permissions[..1];
+        }
         let size = match permissions.chars().next() {
             Some('d') => "",
             _ => parts.get(4).unwrap().clone(),
@@ -199,6 +264,7 @@ fn dir_long(args: &[&str], expected: &[(&str, &str, &str)]) -> Result<()> {
 
 // --------------------------------------------------
 #[test]
+#[cfg(unix)]
 fn dir1_long() -> Result<()> {
     dir_long(
         &["-l", "tests/inputs"],
@@ -212,10 +278,13 @@ fn dir1_long() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir1_long_all() -> Result<()> {
     dir_long(
         &["-la", "tests/inputs"],
         &[
+            ("tests/inputs/.", "d", ""),
+            ("tests/inputs/..", "d", ""),
             ("tests/inputs/empty.txt", "-rw-r--r--", "0"),
             ("tests/inputs/bustle.txt", "-rw-r--r--", "193"),
             ("tests/inputs/fox.txt", "-rw-------", "45"),
@@ -226,6 +295,7 @@ fn dir1_long_all() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir2_long() -> Result<()> {
     dir_long(
         &["--long", "tests/inputs/dir"],
@@ -234,12 +304,180 @@ fn dir2_long() -> Result<()> {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir2_long_all() -> Result<()> {
     dir_long(
         &["tests/inputs/dir", "--long", "--all"],
         &[
+            ("tests/inputs/dir/.", "d", ""),
+            ("tests/inputs/dir/..", "d", ""),
             ("tests/inputs/dir/spiders.txt", "-rw-r--r--", "45"),
             ("tests/inputs/dir/.gitkeep", "-rw-r--r--", "0"),
         ],
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn recursive() -> Result<()> {
+    // ディレクトリごとに見出しを付け、空行で区切って表示する
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("sub/deeper"))?;
+    fs::create_dir(root.join("empty"))?;
+    fs::write(root.join("a.txt"), "a")?;
+    fs::write(root.join("sub/b.txt"), "b")?;
+    fs::write(root.join("sub/deeper/c.txt"), "c")?;
+
+    let root = root.display();
+    let expected = format!(
+        "{root}:\n{root}/a.txt\n{root}/empty\n{root}/sub\n\
+         \n{root}/empty:\n\
+         \n{root}/sub:\n{root}/sub/b.txt\n{root}/sub/deeper\n\
+         \n{root}/sub/deeper:\n{root}/sub/deeper/c.txt\n"
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-R", &root.to_string()])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn recursive_all() -> Result<()> {
+    // -aで表示する「.」と「..」はたどらない
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::create_dir(root.join("sub"))?;
+    fs::write(root.join(".hidden"), "")?;
+
+    let root = root.display();
+    let expected = format!(
+        "{root}:\n{root}/.\n{root}/..\n{root}/.hidden\n{root}/sub\n\
+         \n{root}/sub:\n{root}/sub/.\n{root}/sub/..\n"
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-Ra", &root.to_string()])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn recursive_long_total() -> Result<()> {
+    // 空のディレクトリにも「total 0」を表示する
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::create_dir(root.join("empty"))?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-Rl", &root.display().to_string()])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], format!("{}:", root.display()));
+    assert!(lines[1].starts_with("total "));
+    assert_eq!(
+        &lines[lines.len() - 2..],
+        [format!("{}/empty:", root.display()).as_str(), "total 0"]
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn unreadable_dir() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let locked = dir.path().join("locked");
+    fs::create_dir(&locked)?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000))?;
+
+    // rootなど権限に関係なく読める場合は確認できない
+    if fs::read_dir(&locked).is_ok() {
+        return Ok(());
+    }
+
+    // 読めないディレクトリはエラーを表示して飛ばし、ほかのパスは表示する
+    let expected = format!("lsr: {}: Permission denied", locked.display());
+    let output = Command::cargo_bin(PRG)?.arg(&locked).arg(BUSTLE).output()?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755))?;
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, format!("{}\n", BUSTLE));
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(&expected), "{}", stderr);
+    assert!(!stderr.contains("panicked"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn directory() -> Result<()> {
+    // -dの場合はディレクトリの中身ではなくディレクトリ自体を表示する
+    Command::cargo_bin(PRG)?
+        .args(["-d", "tests/inputs"])
+        .assert()
+        .success()
+        .stdout("tests/inputs\n");
+
+    // -Rを指定しても中には入らない
+    Command::cargo_bin(PRG)?
+        .args(["-dR", "tests/inputs", FOX])
+        .assert()
+        .success()
+        .stdout("tests/inputs\ntests/inputs/fox.txt\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn directory_long() -> Result<()> {
+    // 合計ブロック数は表示せず、ディレクトリ自体のパーミッションを表示する
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["-dl", "tests/inputs"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().filter(|s| !s.is_empty()).collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("drwx"), "{}", lines[0]);
+    assert!(lines[0].ends_with(" tests/inputs"), "{}", lines[0]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn inode() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    // -iの場合は名前の前にinode番号を表示する
+    let ino = fs::symlink_metadata(FOX)?.ino().to_string();
+    Command::cargo_bin(PRG)?
+        .args(["-i", FOX])
+        .assert()
+        .success()
+        .stdout(format!("{ino} {FOX}\n"));
+
+    // -lの場合は先頭の列に表示する
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["-il", FOX])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let parts: Vec<_> = stdout.split_whitespace().collect();
+    assert!(parts[0].chars().all(|c| c.is_ascii_digit()), "{}", stdout);
+    assert_eq!(parts[0], ino);
+    assert!(parts[1].starts_with("-rw"), "{}", stdout);
+    assert_eq!(parts.last(), Some(&FOX));
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+pub type MyResult<T> = Result<T, Box<dyn Error>>;
+
+/// 「-」の場合は標準入力を、それ以外の場合はファイルを開く
+pub fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+/// 0より大きい整数を解釈する。解釈できない場合は値そのものをエラーにする
+pub fn parse_positive_int(val: &str) -> MyResult<usize> {
+    match val.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, parse_positive_int};
+
+    #[test]
+    fn test_open() {
+        // 「-」は標準入力として開く
+        assert!(open("-").is_ok());
+
+        let mut line = String::new();
+        let res = open("Cargo.toml");
+        assert!(res.is_ok());
+        res.unwrap().read_line(&mut line).unwrap();
+        assert_eq!(line, "[package]\n");
+
+        // 存在しないファイルはエラー
+        let res = open("no-such-file");
+        assert!(res.is_err());
+        assert!(res.err().unwrap().to_string().contains("(os error 2)"));
+    }
+
+    #[test]
+    fn test_parse_positive_int() {
+        let res = parse_positive_int("3");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 3);
+
+        let res = parse_positive_int("foo");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "foo".to_string());
+
+        let res = parse_positive_int("0");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "0".to_string());
+    }
+}